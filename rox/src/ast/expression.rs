@@ -1,4 +1,8 @@
-use super::{operator::Operator, span::Span};
+use super::{
+    operator::Operator,
+    span::Span,
+    statement::{Arity, Parameter, Statement},
+};
 use crate::lexer::token::Token;
 use bumpalo::boxed::Box;
 use serde::{Deserialize, Serialize};
@@ -17,6 +21,10 @@ pub enum LiteralValue<'alloc> {
 #[derive(Debug, Serialize)]
 pub struct StringLiteral<'alloc> {
     pub span: Span,
+    /// Source text including the quotes and escape sequences. Interpolation
+    /// parts leave out the quotes, see [`InterpolationPart::String`].
+    pub raw: &'alloc str,
+    /// Contents with escape sequences decoded
     pub value: &'alloc str,
 }
 
@@ -55,12 +63,24 @@ pub enum Expression<'alloc> {
     Assignment(Box<'alloc, Assignment<'alloc>>),
     Binary(Box<'alloc, Binary<'alloc>>),
     Call(Box<'alloc, Call<'alloc>>),
+    CompoundAssignment(Box<'alloc, CompoundAssignment<'alloc>>),
+    Get(Box<'alloc, Get<'alloc>>),
     Grouping(Box<'alloc, Grouping<'alloc>>),
+    Index(Box<'alloc, Index<'alloc>>),
+    Interpolation(Box<'alloc, Interpolation<'alloc>>),
+    Lambda(Box<'alloc, Lambda<'alloc>>),
+    List(Box<'alloc, List<'alloc>>),
     Literal(Box<'alloc, Literal<'alloc>>),
     Logical(Box<'alloc, Logical<'alloc>>),
+    Map(Box<'alloc, Map<'alloc>>),
+    Range(Box<'alloc, Range<'alloc>>),
+    Super(Box<'alloc, Super<'alloc>>),
     Ternary(Box<'alloc, Ternary<'alloc>>),
+    This(Box<'alloc, This>),
     Unary(Box<'alloc, Unary<'alloc>>),
+    Update(Box<'alloc, Update<'alloc>>),
     Variable(Box<'alloc, Variable<'alloc>>),
+    Yield(Box<'alloc, Yield<'alloc>>),
 }
 
 #[cfg_attr(test, derive(PartialEq))]
@@ -87,6 +107,32 @@ pub struct Call<'alloc> {
     pub callee: Expression<'alloc>,
     pub arguments: Vec<Expression<'alloc>>,
     pub end_paren: Token,
+    /// `callee?.(arguments)`, evaluates to nil without calling when the
+    /// callee is nil
+    pub optional: bool,
+}
+
+/// `target op= value`, e.g. `x += 1`.
+/// The target is evaluated once, before the value.
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct CompoundAssignment<'alloc> {
+    pub span: Span,
+    pub target: Expression<'alloc>,
+    pub value: Expression<'alloc>,
+    pub operator: Operator,
+}
+
+/// `object.name`, or `object?.name` which evaluates to nil without reading
+/// the field when the object is nil. The rest of the chain is skipped too,
+/// so `a?.b.c` is nil rather than an error when `a` is nil.
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct Get<'alloc> {
+    pub span: Span,
+    pub object: Expression<'alloc>,
+    pub name: &'alloc str,
+    pub optional: bool,
 }
 
 #[cfg_attr(test, derive(PartialEq))]
@@ -96,6 +142,58 @@ pub struct Grouping<'alloc> {
     pub expression: Expression<'alloc>,
 }
 
+/// `object[index]`, the span covers the closing bracket so that runtime
+/// errors such as an out of bounds index point at the whole subscript
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct Index<'alloc> {
+    pub span: Span,
+    pub object: Expression<'alloc>,
+    pub index: Expression<'alloc>,
+}
+
+/// `"Hello ${name}, you are ${age + 1}"`
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct Interpolation<'alloc> {
+    pub span: Span,
+    pub parts: Vec<InterpolationPart<'alloc>>,
+}
+
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub enum InterpolationPart<'alloc> {
+    /// Text between the quotes and the embedded expressions. Unlike a string
+    /// literal its `raw` text doesn't include the `"`, `${` or `}` delimiters.
+    String(StringLiteral<'alloc>),
+    Expression(Expression<'alloc>),
+}
+
+/// `fun (a, b) { return a + b; }`, an anonymous function.
+/// Parameters and body are represented as in `statement::Function`.
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct Lambda<'alloc> {
+    pub span: Span,
+    pub params: Vec<Parameter<'alloc>>,
+    pub body: Statement<'alloc>,
+    pub is_generator: bool,
+}
+
+impl Lambda<'_> {
+    pub fn arity(&self) -> Arity {
+        Arity::of(&self.params)
+    }
+}
+
+/// `[1, 2, 3]`
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct List<'alloc> {
+    pub span: Span,
+    pub elements: Vec<Expression<'alloc>>,
+}
+
 #[cfg_attr(test, derive(PartialEq))]
 #[derive(Debug, Serialize)]
 pub struct Literal<'alloc> {
@@ -112,6 +210,40 @@ pub struct Logical<'alloc> {
     pub operator: Operator,
 }
 
+/// `{"a": 1, "b": 2}`. Entries are kept in source order, which is also the
+/// iteration order of the resulting map.
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct Map<'alloc> {
+    pub span: Span,
+    pub entries: Vec<MapEntry<'alloc>>,
+}
+
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct MapEntry<'alloc> {
+    pub span: Span,
+    pub key: Expression<'alloc>,
+    pub value: Expression<'alloc>,
+}
+
+/// `start..end`, the numbers from `start` up to but excluding `end`
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct Range<'alloc> {
+    pub span: Span,
+    pub start: Expression<'alloc>,
+    pub end: Expression<'alloc>,
+}
+
+/// `super.method`, the superclass's method bound to `this`
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct Super<'alloc> {
+    pub span: Span,
+    pub method: &'alloc str,
+}
+
 #[cfg_attr(test, derive(PartialEq))]
 #[derive(Debug, Serialize)]
 pub struct Ternary<'alloc> {
@@ -121,6 +253,12 @@ pub struct Ternary<'alloc> {
     pub false_branch: Expression<'alloc>,
 }
 
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct This {
+    pub span: Span,
+}
+
 #[cfg_attr(test, derive(PartialEq))]
 #[derive(Debug, Serialize)]
 pub struct Unary<'alloc> {
@@ -129,6 +267,17 @@ pub struct Unary<'alloc> {
     pub operator: Operator,
 }
 
+/// Prefix or postfix `++`/`--`.
+/// A prefix update evaluates to the new value, a postfix one to the old value.
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct Update<'alloc> {
+    pub span: Span,
+    pub target: Expression<'alloc>,
+    pub operator: Operator,
+    pub prefix: bool,
+}
+
 #[cfg_attr(test, derive(PartialEq))]
 #[derive(Debug, Serialize)]
 pub struct Variable<'alloc> {
@@ -136,18 +285,63 @@ pub struct Variable<'alloc> {
     pub name: &'alloc str,
 }
 
+/// `yield value`, suspends the enclosing generator and hands `value` to
+/// the caller of `next()`. Evaluates to the argument of the `next()` call
+/// that resumes it.
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct Yield<'alloc> {
+    pub span: Span,
+    /// `None` for a bare `yield`, which yields `nil`
+    pub value: Option<Expression<'alloc>>,
+}
+
 impl<'alloc> Expression<'alloc> {
+    /// Whether the expression can appear on the left of `=`, `+=` or `++`.
+    /// A property or index can't be assigned when any link of its chain is
+    /// optional, like `a?.b.c`, as the whole chain may short-circuit to nil.
+    pub fn is_assignment_target(&self) -> bool {
+        match self {
+            Expression::Variable(_) => true,
+            Expression::Get(_) | Expression::Index(_) => !self.has_optional_link(),
+            _ => false,
+        }
+    }
+
+    /// Whether a `?.` appears anywhere in the chain of gets, indexes and
+    /// calls that ends in this expression
+    fn has_optional_link(&self) -> bool {
+        match self {
+            Expression::Get(get) => get.optional || get.object.has_optional_link(),
+            Expression::Index(index) => index.object.has_optional_link(),
+            Expression::Call(call) => call.optional || call.callee.has_optional_link(),
+            _ => false,
+        }
+    }
+
     pub fn span(&self) -> Span {
         match self {
             Expression::Assignment(assignment) => assignment.span,
             Expression::Binary(binary) => binary.span,
             Expression::Call(call) => call.span,
+            Expression::CompoundAssignment(assignment) => assignment.span,
+            Expression::Get(get) => get.span,
             Expression::Grouping(grouping) => grouping.span,
+            Expression::Index(index) => index.span,
+            Expression::Interpolation(interpolation) => interpolation.span,
+            Expression::Lambda(lambda) => lambda.span,
+            Expression::List(list) => list.span,
             Expression::Literal(literal) => literal.span,
             Expression::Logical(logical) => logical.span,
+            Expression::Map(map) => map.span,
+            Expression::Range(range) => range.span,
+            Expression::Super(super_) => super_.span,
             Expression::Ternary(ternary) => ternary.span,
+            Expression::This(this) => this.span,
             Expression::Unary(unary) => unary.span,
+            Expression::Update(update) => update.span,
             Expression::Variable(variable) => variable.span,
+            Expression::Yield(yield_) => yield_.span,
         }
     }
 }
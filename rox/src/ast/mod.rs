@@ -1,5 +1,6 @@
 pub mod expression;
 pub mod operator;
+pub mod pattern;
 pub mod span;
 pub mod statement;
 
@@ -4,17 +4,33 @@ use serde::{Deserialize, Serialize};
 #[cfg_attr(test, derive(PartialEq, Eq))]
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum Operator {
+    Ampersand(Span),
     And(Span),
     Bang(Span),
     BangEqual(Span),
+    Caret(Span),
     EqualEqual(Span),
     Greater(Span),
     GreaterEqual(Span),
+    GreaterGreater(Span),
     Less(Span),
     LessEqual(Span),
+    LessLess(Span),
     Minus(Span),
+    MinusEqual(Span),
+    MinusMinus(Span),
     Or(Span),
+    Percent(Span),
+    Pipe(Span),
     Plus(Span),
+    PlusEqual(Span),
+    PlusPlus(Span),
+    QuestionQuestion(Span),
     Slash(Span),
+    SlashEqual(Span),
     Star(Span),
+    StarEqual(Span),
+    StarStar(Span),
+    Tilde(Span),
+    TildeSlash(Span),
 }
@@ -0,0 +1,130 @@
+use super::{expression::LiteralValue, span::Span};
+use bumpalo::boxed::Box;
+use serde::Serialize;
+
+/// The left hand side of a `match` arm or the target of a declaration
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+#[serde(tag = "type")]
+pub enum Pattern<'alloc> {
+    Binding(Box<'alloc, BindingPattern<'alloc>>),
+    List(Box<'alloc, ListPattern<'alloc>>),
+    Literal(Box<'alloc, LiteralPattern<'alloc>>),
+    Map(Box<'alloc, MapPattern<'alloc>>),
+    Variant(Box<'alloc, VariantPattern<'alloc>>),
+    Wildcard(Box<'alloc, WildcardPattern>),
+}
+
+/// A name that matches anything and binds the value to it
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct BindingPattern<'alloc> {
+    pub span: Span,
+    pub name: &'alloc str,
+}
+
+/// `[a, b]`, matches a list with exactly as many elements as the pattern
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct ListPattern<'alloc> {
+    pub span: Span,
+    pub elements: Vec<Pattern<'alloc>>,
+}
+
+/// A string, number, boolean or nil literal, matched by equality
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct LiteralPattern<'alloc> {
+    pub span: Span,
+    pub value: LiteralValue<'alloc>,
+}
+
+/// `{x, y: [a, b]}`, matches a map that has every listed key. A key on
+/// its own binds the value to a variable of the same name.
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct MapPattern<'alloc> {
+    pub span: Span,
+    pub entries: Vec<MapPatternEntry<'alloc>>,
+}
+
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct MapPatternEntry<'alloc> {
+    pub span: Span,
+    pub key: &'alloc str,
+    pub pattern: Pattern<'alloc>,
+}
+
+/// `Color.Red`, matches that variant of an enum
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct VariantPattern<'alloc> {
+    pub span: Span,
+    pub enum_name: &'alloc str,
+    pub variant: &'alloc str,
+}
+
+/// `_`, matches anything without binding it
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct WildcardPattern {
+    pub span: Span,
+}
+
+impl<'alloc> Pattern<'alloc> {
+    pub fn span(&self) -> Span {
+        match self {
+            Pattern::Binding(binding) => binding.span,
+            Pattern::List(list) => list.span,
+            Pattern::Literal(literal) => literal.span,
+            Pattern::Map(map) => map.span,
+            Pattern::Variant(variant) => variant.span,
+            Pattern::Wildcard(wildcard) => wildcard.span,
+        }
+    }
+
+    /// Whether the pattern matches every value, making later arms unreachable
+    pub fn is_irrefutable(&self) -> bool {
+        matches!(self, Pattern::Binding(_) | Pattern::Wildcard(_))
+    }
+
+    /// Every name the pattern binds, including in nested patterns
+    pub fn bindings(&self) -> Vec<&BindingPattern<'alloc>> {
+        let mut bindings = Vec::new();
+        self.collect_bindings(&mut bindings);
+        bindings
+    }
+
+    fn collect_bindings<'a>(&'a self, bindings: &mut Vec<&'a BindingPattern<'alloc>>) {
+        match self {
+            Pattern::Binding(binding) => bindings.push(binding),
+            Pattern::List(list) => {
+                for element in list.elements.iter() {
+                    element.collect_bindings(bindings);
+                }
+            }
+            Pattern::Map(map) => {
+                for entry in map.entries.iter() {
+                    entry.pattern.collect_bindings(bindings);
+                }
+            }
+            Pattern::Literal(_) | Pattern::Variant(_) | Pattern::Wildcard(_) => {}
+        }
+    }
+
+    /// Span of the first literal or enum variant in the pattern,
+    /// declarations can only destructure
+    pub fn first_value_pattern(&self) -> Option<Span> {
+        match self {
+            Pattern::Literal(literal) => Some(literal.span),
+            Pattern::Variant(variant) => Some(variant.span),
+            Pattern::List(list) => list.elements.iter().find_map(Pattern::first_value_pattern),
+            Pattern::Map(map) => map
+                .entries
+                .iter()
+                .find_map(|entry| entry.pattern.first_value_pattern()),
+            Pattern::Binding(_) | Pattern::Wildcard(_) => None,
+        }
+    }
+}
@@ -22,6 +22,11 @@ impl Span {
         debug_assert!(self.from <= self.to);
         self
     }
+
+    /// The line the span starts on, counted from 1 like token lines
+    pub fn line(&self, source: &str) -> usize {
+        source[..self.from].matches('\n').count() + 1
+    }
 }
 
 pub trait Spanned {
@@ -1,20 +1,27 @@
 use crate::lexer::token::Token;
 
-use super::{expression::Expression as Expr, span::Span};
+use super::{expression::Expression as Expr, pattern::Pattern, span::Span};
 use bumpalo::{boxed::Box, collections::Vec as BumpVec};
 use serde::Serialize;
+use std::fmt::Display;
 
 #[cfg_attr(test, derive(PartialEq))]
 #[derive(Debug, Serialize)]
 #[serde(tag = "type")]
 pub enum Statement<'alloc> {
     Block(Box<'alloc, Block<'alloc>>),
+    Class(Box<'alloc, Class<'alloc>>),
+    Enum(Box<'alloc, Enum<'alloc>>),
     Expression(Box<'alloc, Expression<'alloc>>),
     For(Box<'alloc, For<'alloc>>),
+    ForIn(Box<'alloc, ForIn<'alloc>>),
     Function(Box<'alloc, Function<'alloc>>),
     If(Box<'alloc, If<'alloc>>),
+    Match(Box<'alloc, Match<'alloc>>),
     Print(Box<'alloc, Print<'alloc>>),
     Return(Box<'alloc, Return<'alloc>>),
+    Throw(Box<'alloc, Throw<'alloc>>),
+    Try(Box<'alloc, Try<'alloc>>),
     While(Box<'alloc, While<'alloc>>),
     Declaration(Box<'alloc, Declaration<'alloc>>),
 }
@@ -29,14 +36,94 @@ pub struct Block<'alloc> {
 
 #[cfg_attr(test, derive(PartialEq))]
 #[derive(Debug, Serialize)]
-pub struct Declaration<'alloc> {
+pub struct Class<'alloc> {
     #[serde(flatten)]
     pub span: Span,
+    /// Text of the `///` comments directly above the class
+    pub doc: Option<&'alloc str>,
+    pub name: Token,
+    /// `class Name < Superclass`
+    pub superclass: Option<Token>,
+    pub methods: Vec<Method<'alloc>>,
+}
 
+/// A method in a class body. Parameters and body are represented as in
+/// `Function`, a getter has no parameters.
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct Method<'alloc> {
+    #[serde(flatten)]
+    pub span: Span,
+    pub kind: MethodKind,
     pub name: Token,
+    pub params: Vec<Parameter<'alloc>>,
+    pub body: Statement<'alloc>,
+    pub is_generator: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum MethodKind {
+    /// `name { ... }`, runs when the property is read
+    Getter,
+    /// `init(params) { ... }`, runs when an instance is created and always
+    /// returns `this`
+    Initializer,
+    /// `name(params) { ... }`
+    Method,
+    /// `set name(value) { ... }`, runs when the property is assigned
+    Setter,
+    /// `class name(params) { ... }`, called on the class itself
+    Static,
+}
+
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct Declaration<'alloc> {
+    #[serde(flatten)]
+    pub span: Span,
+
+    /// Text of the `///` comments directly above the declaration
+    pub doc: Option<&'alloc str>,
+    pub kind: DeclarationKind,
+    /// A plain name, or a list or map pattern destructuring the value
+    pub target: Pattern<'alloc>,
+    /// Always present for `const` and destructuring, a plain `let` or `var`
+    /// without one starts as `nil`
     pub value: Option<Expr<'alloc>>,
 }
 
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Clone, Copy, Serialize)]
+pub enum DeclarationKind {
+    /// Cannot be reassigned after its initializer runs
+    Const,
+    /// Block-scoped, reading it before its declaration runs is an error
+    Let,
+    /// Block or function-scoped depending on `LanguageOptions::var_scope`
+    Var,
+}
+
+/// `enum Color { Red, Green, Blue }`, binds `Color` to a namespace whose
+/// variants are distinct values, equal only to themselves
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct Enum<'alloc> {
+    #[serde(flatten)]
+    pub span: Span,
+    /// Text of the `///` comments directly above the enum
+    pub doc: Option<&'alloc str>,
+    pub name: Token,
+    pub variants: Vec<EnumVariant<'alloc>>,
+}
+
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct EnumVariant<'alloc> {
+    #[serde(flatten)]
+    pub span: Span,
+    pub name: &'alloc str,
+}
+
 #[cfg_attr(test, derive(PartialEq))]
 #[derive(Debug, Serialize)]
 pub struct Expression<'alloc> {
@@ -56,14 +143,84 @@ pub struct For<'alloc> {
     pub body: Statement<'alloc>,
 }
 
+/// `for (variable in iterable) body` over a list, the keys of a map, the
+/// characters of a string or a range. Each iteration gets its own scope
+/// so closures created in the body capture that iteration's element.
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct ForIn<'alloc> {
+    #[serde(flatten)]
+    pub span: Span,
+    pub variable: Token,
+    pub iterable: Expr<'alloc>,
+    pub body: Statement<'alloc>,
+}
+
 #[cfg_attr(test, derive(PartialEq))]
 #[derive(Debug, Serialize)]
 pub struct Function<'alloc> {
     #[serde(flatten)]
     pub span: Span,
+    /// Text of the `///` comments directly above the function
+    pub doc: Option<&'alloc str>,
     pub name: Token,
-    pub params: Vec<Token>,
+    pub params: Vec<Parameter<'alloc>>,
     pub body: Statement<'alloc>,
+    /// Set when the body, outside any nested function, contains `yield`.
+    /// Calling a generator returns an iterator instead of running the body.
+    pub is_generator: bool,
+}
+
+/// A function parameter: `name`, `name = default` or a trailing `...name`
+/// collecting the remaining arguments. Defaults are evaluated at call time.
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct Parameter<'alloc> {
+    #[serde(flatten)]
+    pub span: Span,
+    pub name: Token,
+    pub default: Option<Expr<'alloc>>,
+    pub rest: bool,
+}
+
+/// The number of arguments a function accepts
+#[cfg_attr(test, derive(PartialEq, Eq))]
+#[derive(Debug, Clone, Copy)]
+pub struct Arity {
+    pub min: usize,
+    /// `None` when a rest parameter takes any number of extra arguments
+    pub max: Option<usize>,
+}
+
+impl Arity {
+    /// Parameters with a default or the rest parameter are optional, the
+    /// parser guarantees that they come after the required ones
+    pub fn of(params: &[Parameter]) -> Self {
+        let min = params
+            .iter()
+            .take_while(|param| param.default.is_none() && !param.rest)
+            .count();
+        let max = if params.iter().any(|param| param.rest) {
+            None
+        } else {
+            Some(params.len())
+        };
+        Self { min, max }
+    }
+
+    pub fn accepts(&self, argument_count: usize) -> bool {
+        argument_count >= self.min && self.max.map_or(true, |max| argument_count <= max)
+    }
+}
+
+impl Display for Arity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{}", max),
+            Some(max) => write!(f, "{} to {}", self.min, max),
+            None => write!(f, "at least {}", self.min),
+        }
+    }
 }
 
 #[cfg_attr(test, derive(PartialEq))]
@@ -76,6 +233,26 @@ pub struct If<'alloc> {
     pub else_branch: Option<Statement<'alloc>>,
 }
 
+/// `match (subject) { pattern => body, ... }`, runs the body of the first
+/// arm whose pattern matches the subject
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct Match<'alloc> {
+    #[serde(flatten)]
+    pub span: Span,
+    pub subject: Expr<'alloc>,
+    pub arms: Vec<MatchArm<'alloc>>,
+}
+
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct MatchArm<'alloc> {
+    #[serde(flatten)]
+    pub span: Span,
+    pub pattern: Pattern<'alloc>,
+    pub body: Statement<'alloc>,
+}
+
 #[cfg_attr(test, derive(PartialEq))]
 #[derive(Debug, Serialize)]
 pub struct Print<'alloc> {
@@ -87,11 +264,45 @@ pub struct Print<'alloc> {
 #[cfg_attr(test, derive(PartialEq))]
 #[derive(Debug, Serialize)]
 pub struct Return<'alloc> {
+    #[serde(flatten)]
+    pub span: Span,
+    /// `None` for a bare `return;`, which evaluates to `nil`, or to `this`
+    /// in an initializer.
+    pub value: Option<Expr<'alloc>>,
+}
+
+/// `throw value;`, any value can be thrown
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct Throw<'alloc> {
     #[serde(flatten)]
     pub span: Span,
     pub value: Expr<'alloc>,
 }
 
+/// `try { } catch (e) { } finally { }`, at least one of `catch` and
+/// `finally` is present. The `finally` block runs however the try and
+/// catch blocks are left, including by `return` or an uncaught exception.
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct Try<'alloc> {
+    #[serde(flatten)]
+    pub span: Span,
+    pub body: Statement<'alloc>,
+    pub catch: Option<Catch<'alloc>>,
+    pub finally: Option<Statement<'alloc>>,
+}
+
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct Catch<'alloc> {
+    #[serde(flatten)]
+    pub span: Span,
+    /// Bound to the thrown value while the body runs
+    pub param: Token,
+    pub body: Statement<'alloc>,
+}
+
 #[cfg_attr(test, derive(PartialEq))]
 #[derive(Debug, Serialize)]
 pub struct While<'alloc> {
@@ -101,16 +312,28 @@ pub struct While<'alloc> {
     pub body: Statement<'alloc>,
 }
 
+impl Function<'_> {
+    pub fn arity(&self) -> Arity {
+        Arity::of(&self.params)
+    }
+}
+
 impl Statement<'_> {
     pub fn span(&self) -> Span {
         match self {
             Statement::Block(block) => block.span,
+            Statement::Class(class) => class.span,
+            Statement::Enum(enum_) => enum_.span,
             Statement::Expression(expr) => expr.span,
             Statement::For(for_) => for_.span,
+            Statement::ForIn(for_in) => for_in.span,
             Statement::Function(fun) => fun.span,
             Statement::If(if_) => if_.span,
+            Statement::Match(match_) => match_.span,
             Statement::Print(print) => print.span,
             Statement::Return(ret) => ret.span,
+            Statement::Throw(throw) => throw.span,
+            Statement::Try(try_) => try_.span,
             Statement::While(while_) => while_.span,
             Statement::Declaration(decl) => decl.span,
         }
@@ -1,12 +1,38 @@
-expression     → equality ;
+expression     → assignment ;
+assignment     → target ( "=" | "+=" | "-=" | "*=" | "/=" ) assignment
+               | "yield" assignment?
+               | nil_coalesce ;
+target         → IDENTIFIER | call "[" expression "]" | call "." IDENTIFIER ;
+nil_coalesce   → logic_or ( "??" nil_coalesce )? ;
+logic_or       → logic_and ( "or" logic_and )* ;
+logic_and      → bit_or ( "and" bit_or )* ;
+bit_or         → bit_xor ( "|" bit_xor )* ;
+bit_xor        → bit_and ( "^" bit_and )* ;
+bit_and        → equality ( "&" equality )* ;
 equality       → comparison ( ( "!=" | "==" ) comparison )* ;
-comparison     → term ( ( ">" | ">=" | "<" | "<=" ) term )* ;
+comparison     → range ( ( ">" | ">=" | "<" | "<=" ) range )* ;
+range          → shift ( ".." shift )? ;
+shift          → term ( ( "<<" | ">>" ) term )* ;
 term           → factor ( ( "-" | "+" ) factor )* ;
-factor         → unary ( ( "/" | "*" ) unary )* ;
-unary          → ( "!" | "-" ) unary
-               | primary ;
-primary        → NUMBER | STRING | "true" | "false" | "nil"
-               | "(" expression ")" ;
+factor         → unary ( ( "/" | "*" | "%" | "~/" ) unary )* ;
+unary          → ( "!" | "-" | "~" | "++" | "--" ) unary
+               | exponent ;
+exponent       → postfix ( "**" unary )? ;
+postfix        → call ( "++" | "--" )? ;
+call           → primary ( "(" arguments? ")" | "[" expression "]"
+                         | ( "." | "?." ) IDENTIFIER | "?." "(" arguments? ")" )* ;
+arguments      → expression ( "," expression )* ;
+primary        → NUMBER | STRING | "true" | "false" | "nil" | IDENTIFIER
+               | "this" | "super" "." IDENTIFIER
+               | "(" expression ")" | interpolation | lambda | list | map ;
+list           → "[" ( expression ( "," expression )* ","? )? "]" ;
+map            → "{" ( entry ( "," entry )* ","? )? "}" ;
+entry          → expression ":" expression ;
+lambda         → "fun" "(" parameters? ")" block ;
+parameters     → parameter ( "," parameter )* ;
+parameter      → IDENTIFIER ( "=" expression )? | "..." IDENTIFIER ;
+interpolation  → INTERPOLATION_START expression
+                 ( INTERPOLATION_MIDDLE expression )* INTERPOLATION_END ;
 
 
-((2 * 3) + 1) > 6
\ No newline at end of file
+((2 * 3) + 1) > 6
@@ -0,0 +1,143 @@
+/// An invalid escape sequence, located by byte offsets into the string
+/// contents that were passed to [`unescape`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EscapeError {
+    pub from: usize,
+    pub to: usize,
+    pub message: String,
+}
+
+impl EscapeError {
+    fn new(from: usize, to: usize, message: String) -> Self {
+        Self { from, to, message }
+    }
+}
+
+/// Decode the escape sequences in the contents of a string literal, i.e. the
+/// text between the quotes.
+///
+/// Supported escapes are `\n`, `\r`, `\t`, `\"`, `\\`, `\$` and `\u{...}` with
+/// one to six hex digits. Every invalid escape is reported, not just the first.
+pub fn unescape(raw: &str) -> Result<String, Vec<EscapeError>> {
+    let mut value = String::with_capacity(raw.len());
+    let mut errors = Vec::new();
+    let mut chars = raw.char_indices().peekable();
+
+    while let Some((from, c)) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+
+        let Some((escape_at, escape)) = chars.next() else {
+            errors.push(EscapeError::new(
+                from,
+                raw.len(),
+                "Incomplete escape sequence".to_string(),
+            ));
+            break;
+        };
+        let escape_end = escape_at + escape.len_utf8();
+
+        match escape {
+            'n' => value.push('\n'),
+            'r' => value.push('\r'),
+            't' => value.push('\t'),
+            '"' => value.push('"'),
+            '\\' => value.push('\\'),
+            '$' => value.push('$'),
+            'u' => match unescape_unicode(raw, &mut chars) {
+                Ok(c) => value.push(c),
+                Err((to, message)) => {
+                    errors.push(EscapeError::new(from, to.max(escape_end), message))
+                }
+            },
+            _ => errors.push(EscapeError::new(
+                from,
+                escape_end,
+                format!("Invalid escape sequence '\\{}'", escape),
+            )),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(value)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Decode the `{...}` part of a `\u{...}` escape. On failure returns the end
+/// offset of the malformed escape along with the message.
+fn unescape_unicode(
+    raw: &str,
+    chars: &mut std::iter::Peekable<std::str::CharIndices>,
+) -> Result<char, (usize, String)> {
+    match chars.peek() {
+        Some(&(_, '{')) => {
+            chars.next();
+        }
+        Some(&(at, _)) => {
+            return Err((at, "Expected '{' after '\\u'".to_string()));
+        }
+        None => return Err((raw.len(), "Expected '{' after '\\u'".to_string())),
+    }
+
+    let mut digits = String::new();
+    loop {
+        match chars.next() {
+            Some((at, '}')) => {
+                let end = at + 1;
+                if digits.is_empty() || digits.len() > 6 {
+                    return Err((
+                        end,
+                        "Unicode escape must have between 1 and 6 hex digits".to_string(),
+                    ));
+                }
+                let code_point = u32::from_str_radix(&digits, 16)
+                    .map_err(|_| (end, "Invalid unicode escape".to_string()))?;
+                return char::from_u32(code_point).ok_or_else(|| {
+                    (
+                        end,
+                        format!("'{:X}' is not a valid unicode code point", code_point),
+                    )
+                });
+            }
+            Some((_, c)) if c.is_ascii_hexdigit() => digits.push(c),
+            Some((at, c)) => {
+                return Err((
+                    at + c.len_utf8(),
+                    format!("Invalid character '{}' in unicode escape", c),
+                ));
+            }
+            None => return Err((raw.len(), "Unterminated unicode escape".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unescape_simple_escapes() {
+        assert_eq!(
+            unescape(r#"a\nb\tc\"d\\e\r\${"#),
+            Ok("a\nb\tc\"d\\e\r${".to_string())
+        );
+    }
+
+    #[test]
+    fn test_unescape_unicode() {
+        assert_eq!(unescape(r"\u{41}\u{930}"), Ok("Aर".to_string()));
+    }
+
+    #[test]
+    fn test_unescape_reports_every_invalid_escape() {
+        let errors = unescape(r"ok \q and \u{D800} and \u{}").unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert_eq!((errors[0].from, errors[0].to), (3, 5));
+        assert_eq!((errors[1].from, errors[1].to), (10, 18));
+        assert_eq!((errors[2].from, errors[2].to), (23, 27));
+    }
+}
@@ -10,21 +10,31 @@ pub fn get_default_keywords() -> &'static Keywords {
     DEFAULT_KEYWORDS.get_or_init(|| {
         let mut m = HashMap::new();
         m.insert("and".to_string(), TokenKind::And);
+        m.insert("catch".to_string(), TokenKind::Catch);
         m.insert("class".to_string(), TokenKind::Class);
+        m.insert("const".to_string(), TokenKind::Const);
         m.insert("else".to_string(), TokenKind::Else);
+        m.insert("enum".to_string(), TokenKind::Enum);
         m.insert("false".to_string(), TokenKind::False);
+        m.insert("finally".to_string(), TokenKind::Finally);
         m.insert("fun".to_string(), TokenKind::Fun);
         m.insert("for".to_string(), TokenKind::For);
         m.insert("if".to_string(), TokenKind::If);
+        m.insert("in".to_string(), TokenKind::In);
+        m.insert("let".to_string(), TokenKind::Let);
+        m.insert("match".to_string(), TokenKind::Match);
         m.insert("nil".to_string(), TokenKind::Nil);
         m.insert("or".to_string(), TokenKind::Or);
         m.insert("print".to_string(), TokenKind::Print);
         m.insert("return".to_string(), TokenKind::Return);
         m.insert("super".to_string(), TokenKind::Super);
         m.insert("this".to_string(), TokenKind::This);
+        m.insert("throw".to_string(), TokenKind::Throw);
         m.insert("true".to_string(), TokenKind::True);
+        m.insert("try".to_string(), TokenKind::Try);
         m.insert("var".to_string(), TokenKind::Var);
         m.insert("while".to_string(), TokenKind::While);
+        m.insert("yield".to_string(), TokenKind::Yield);
         m
     })
 }
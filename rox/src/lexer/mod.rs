@@ -1,13 +1,17 @@
 mod keyword;
 mod reader;
 
+pub mod escape;
+pub mod number;
 pub mod token;
 pub mod token_kind;
 
-use std::future;
+use std::{collections::HashMap, future};
 
-use crate::{ast::expression::Literal, lox_error::LoxError};
+use crate::{ast::span::Span, lox_error::LoxError};
+use escape::unescape;
 use keyword::{combine_keywords, get_default_keywords, Keywords};
+use number::{default_numeral_scripts, NumeralScript};
 use reader::Reader;
 use token::Token;
 use token_kind::TokenKind;
@@ -21,8 +25,18 @@ pub struct Lexer<'alloc> {
     source: &'alloc str,
     reader: Reader<'alloc>,
     pub tokens: Vec<Token>,
+    pub doc_comments: Vec<Token>,
+    /// Decoded value of every string segment containing escape sequences,
+    /// keyed by the `from` of its token. Segments without escapes are
+    /// their own source text.
+    pub decoded: HashMap<usize, String>,
     keywords: Keywords,
     errors: Vec<LoxError>,
+    // One entry per interpolated expression being scanned, the offset of
+    // its `${` and a count of the braces opened inside it so we know which
+    // `}` resumes the string
+    interpolations: Vec<(usize, usize)>,
+    numeral_scripts: Vec<NumeralScript>,
 }
 
 pub enum MultiCharToken {
@@ -31,6 +45,10 @@ pub enum MultiCharToken {
     Ident,
     Slash,
     IfEqualElse(TokenKind, TokenKind),
+    IfSlashElse(TokenKind, TokenKind),
+    /// Doubled character, character followed by `=`, or the single character
+    /// on its own, e.g. `++`, `+=` and `+`.
+    IfDoubleIfEqualElse(char, TokenKind, TokenKind, TokenKind),
 }
 
 impl<'alloc> Lexer<'alloc> {
@@ -39,8 +57,12 @@ impl<'alloc> Lexer<'alloc> {
             source,
             reader: Reader::new(source),
             tokens: Vec::new(),
+            doc_comments: Vec::new(),
+            decoded: HashMap::new(),
             keywords: get_default_keywords().clone(),
             errors: Vec::new(),
+            interpolations: Vec::new(),
+            numeral_scripts: default_numeral_scripts(),
         }
     }
     /// Create a new lexer with custom keywords
@@ -51,11 +73,21 @@ impl<'alloc> Lexer<'alloc> {
             source,
             reader: Reader::new(source),
             tokens: Vec::new(),
+            doc_comments: Vec::new(),
+            decoded: HashMap::new(),
             keywords: combine_keywords(keywords),
             errors: Vec::new(),
+            interpolations: Vec::new(),
+            numeral_scripts: default_numeral_scripts(),
         }
     }
 
+    /// Set the scripts, besides ASCII, whose digits are accepted in number literals
+    pub fn with_numeral_scripts(mut self, scripts: Vec<NumeralScript>) -> Self {
+        self.numeral_scripts = scripts;
+        self
+    }
+
     fn add_error(&mut self, error: LoxError) {
         self.errors.push(error);
     }
@@ -94,7 +126,27 @@ impl<'alloc> Lexer<'alloc> {
                 };
                 self.add_token(kind);
             }
-            MultiCharToken::String => self.scan_string(),
+            MultiCharToken::IfSlashElse(if_token, else_token) => {
+                let kind = if self.reader.peek() == Some(&'/') {
+                    self.reader.advance();
+                    if_token
+                } else {
+                    else_token
+                };
+                self.add_token(kind);
+            }
+            MultiCharToken::IfDoubleIfEqualElse(c, double_token, equal_token, else_token) => {
+                let kind = match self.reader.peek() {
+                    Some(&next) if next == c => double_token,
+                    Some(&'=') => equal_token,
+                    _ => else_token,
+                };
+                if kind != else_token {
+                    self.reader.advance();
+                }
+                self.add_token(kind);
+            }
+            MultiCharToken::String => self.scan_string(false),
             MultiCharToken::Number => self.scan_number(),
             MultiCharToken::Ident => self.scan_identifier(),
             MultiCharToken::Slash => self.handle_slash(),
@@ -103,35 +155,230 @@ impl<'alloc> Lexer<'alloc> {
 
     fn handle_slash(&mut self) {
         if self.reader.peek() == Some(&'/') {
+            self.reader.advance();
+            // `///` starts a doc comment but `////` is an ordinary comment
+            let is_doc = self.reader.peek() == Some(&'/') && self.reader.peek_next() != Some('/');
             while self.reader.peek() != Some(&'\n') && self.reader.peek() != None {
                 self.reader.advance();
             }
+            if is_doc {
+                self.doc_comments.push(Token::new(
+                    TokenKind::DocComment,
+                    self.reader.line,
+                    self.reader.start,
+                    self.reader.cursor,
+                ));
+            }
+            self.reader.sync();
+        } else if self.reader.peek() == Some(&'*') {
+            self.skip_block_comment();
+        } else if self.reader.peek() == Some(&'=') {
+            self.reader.advance();
+            self.add_token(TokenKind::SlashEqual);
         } else {
             self.add_token(TokenKind::Slash);
         }
     }
 
-    fn scan_string(&mut self) {
+    /// Skip a `/* ... */` comment, which may contain nested block comments
+    fn skip_block_comment(&mut self) {
+        let opening = Span::new(self.reader.start, self.reader.start + 2);
+        let line = self.reader.line;
+        self.reader.advance();
+        let mut depth = 1;
+
+        while depth > 0 {
+            match self.reader.advance() {
+                Some('/') if self.reader.peek() == Some(&'*') => {
+                    self.reader.advance();
+                    depth += 1;
+                }
+                Some('*') if self.reader.peek() == Some(&'/') => {
+                    self.reader.advance();
+                    depth -= 1;
+                }
+                Some(_) => {}
+                None => {
+                    self.add_error(
+                        LoxError::new(line, format!("[Line {}]: Unterminated block comment", line))
+                            .with_span(opening),
+                    );
+                    break;
+                }
+            }
+        }
+        self.reader.sync();
+    }
+
+    /// Scan string contents up to the closing quote or the next `${`.
+    /// `resumed` is set when the segment starts at the `}` that closes an
+    /// interpolated expression rather than at the opening quote.
+    fn scan_string(&mut self, resumed: bool) {
         while let Some(c) = self.reader.advance() {
-            if c == '"' {
-                self.add_token(TokenKind::String);
-                return;
+            match c {
+                // Skip the escaped character so that `\"` doesn't end the string,
+                // the escape itself is decoded once the segment is complete
+                '\\' => {
+                    self.reader.advance();
+                }
+                '$' if self.reader.peek() == Some(&'{') => {
+                    self.reader.advance();
+                    self.decode_escapes(2);
+                    self.interpolations.push((self.reader.cursor - 2, 0));
+                    self.add_token(if resumed {
+                        TokenKind::InterpolationMiddle
+                    } else {
+                        TokenKind::InterpolationStart
+                    });
+                    return;
+                }
+                '"' => {
+                    self.decode_escapes(1);
+                    self.add_token(if resumed {
+                        TokenKind::InterpolationEnd
+                    } else {
+                        TokenKind::String
+                    });
+                    return;
+                }
+                _ => {}
             }
         }
-        self.add_error(LoxError::new(
-            self.reader.line,
-            format!("[Line {}]: Unterminated string", self.reader.line),
-        ));
+        self.add_error(
+            LoxError::new(
+                self.reader.line,
+                format!("[Line {}]: Unterminated string", self.reader.line),
+            )
+            .with_span(Span::new(self.reader.start, self.reader.cursor)),
+        );
+    }
+
+    /// Decode the string segment that was just scanned, reporting every
+    /// invalid escape sequence, `delimiter_len` being the length of its
+    /// closing `"` or `${`
+    fn decode_escapes(&mut self, delimiter_len: usize) {
+        let contents_from = self.reader.start + 1;
+        let contents = &self.source[contents_from..self.reader.cursor - delimiter_len];
+        if !contents.contains('\\') {
+            return;
+        }
+        match unescape(contents) {
+            Ok(value) => {
+                self.decoded.insert(self.reader.start, value);
+            }
+            Err(errors) => {
+                for error in errors {
+                    self.add_error(
+                        LoxError::new(
+                            self.reader.line,
+                            format!("[Line {}]: {}", self.reader.line, error.message),
+                        )
+                        .with_span(Span::new(
+                            contents_from + error.from,
+                            contents_from + error.to,
+                        )),
+                    );
+                }
+            }
+        }
+    }
+
+    fn number_error(&mut self, message: &str) {
+        self.add_error(
+            LoxError::new(
+                self.reader.line,
+                format!("[Line {}]: {}", self.reader.line, message),
+            )
+            .with_span(Span::new(self.reader.start, self.reader.cursor)),
+        );
+    }
+
+    fn is_script_digit(&self, c: char) -> bool {
+        self.numeral_scripts
+            .iter()
+            .any(|script| script.digit_value(c).is_some())
+    }
+
+    fn is_digit(&self, c: char, radix: u32) -> bool {
+        c.is_digit(radix) || (radix == 10 && self.is_script_digit(c))
+    }
+
+    /// Consume digits of the given radix, allowing single underscores between
+    /// digits. `seen_digit` is set when a digit was consumed before the call.
+    /// Returns whether any digit was consumed.
+    fn scan_digits(&mut self, radix: u32, mut seen_digit: bool) -> bool {
+        let mut consumed = false;
+        let mut after_underscore = false;
+        while let Some(&c) = self.reader.peek() {
+            if self.is_digit(c, radix) {
+                seen_digit = true;
+                consumed = true;
+                after_underscore = false;
+            } else if c == '_' {
+                self.reader.advance();
+                if !seen_digit || after_underscore {
+                    self.number_error("Underscores are only allowed between digits");
+                }
+                after_underscore = true;
+                continue;
+            } else {
+                break;
+            }
+            self.reader.advance();
+        }
+        if after_underscore {
+            self.number_error("Underscores are only allowed between digits");
+        }
+        consumed
     }
 
     fn scan_number(&mut self) {
-        let closure = |c: char| c.is_numeric();
-        self.reader.advance_while(closure);
-        if self.reader.peek() == Some(&'.') {
-            self.reader.advance_while(closure);
+        let errors_before = self.errors.len();
+        let radix = match (
+            &self.source[self.reader.start..self.reader.cursor],
+            self.reader.peek(),
+        ) {
+            ("0", Some('x' | 'X')) => 16,
+            ("0", Some('b' | 'B')) => 2,
+            _ => 10,
+        };
+
+        if radix == 10 {
+            self.scan_digits(10, true);
+            // `0..10` is a range, not a malformed fraction
+            if self.reader.peek() == Some(&'.') && self.reader.peek_next() != Some('.') {
+                self.reader.advance();
+                if !self.scan_digits(10, false) {
+                    self.number_error("Expected digit after '.' in number literal");
+                }
+            }
+            if matches!(self.reader.peek(), Some('e' | 'E')) {
+                self.reader.advance();
+                if matches!(self.reader.peek(), Some('+' | '-')) {
+                    self.reader.advance();
+                }
+                if !self.scan_digits(10, false) {
+                    self.number_error("Expected digit in number literal exponent");
+                }
+            }
+        } else {
+            self.reader.advance();
+            if !self.scan_digits(radix, false) {
+                let prefix = &self.source[self.reader.start..self.reader.cursor];
+                self.number_error(&format!("Expected digit after '{}'", prefix));
+            }
         }
 
-        // NOTE: HANDLE_ERROR
+        // A literal can't run straight into an identifier, e.g. `0b102` or `12px`.
+        // One error is enough per literal, an earlier one is widened to cover it.
+        if matches!(self.reader.peek(), Some(&c) if c.is_alphanumeric() || c == '_') {
+            self.reader
+                .advance_while(|c: char| c.is_alphanumeric() || c == '_');
+            match self.errors.get_mut(errors_before) {
+                Some(error) => error.span = Some(Span::new(self.reader.start, self.reader.cursor)),
+                None => self.number_error("Invalid number literal"),
+            }
+        }
 
         self.add_token(TokenKind::Number);
     }
@@ -141,11 +388,11 @@ impl<'alloc> Lexer<'alloc> {
             .advance_while(|c: char| c.is_alphanumeric() || c == '_');
         let literal = &self.source[self.reader.start..self.reader.cursor];
 
-        let mut kind = TokenKind::Identifier;
-
-        if get_default_keywords().contains_key(literal) {
-            kind = self.keywords[literal];
-        }
+        let kind = self
+            .keywords
+            .get(literal)
+            .copied()
+            .unwrap_or(TokenKind::Identifier);
 
         self.add_token(kind);
     }
@@ -156,33 +403,101 @@ impl<'alloc> Lexer<'alloc> {
                 ' ' | '\r' | '\t' | '\n' => self.reader.sync(),
                 '(' => self.add_token(TokenKind::LeftParen),
                 ')' => self.add_token(TokenKind::RightParen),
-                '{' => self.add_token(TokenKind::LeftBrace),
-                '}' => self.add_token(TokenKind::RightBrace),
+                '{' => {
+                    if let Some((_, depth)) = self.interpolations.last_mut() {
+                        *depth += 1;
+                    }
+                    self.add_token(TokenKind::LeftBrace);
+                }
+                '}' => match self.interpolations.last_mut() {
+                    Some((_, 0)) => {
+                        self.interpolations.pop();
+                        self.scan_string(true);
+                    }
+                    Some((_, depth)) => {
+                        *depth -= 1;
+                        self.add_token(TokenKind::RightBrace);
+                    }
+                    None => self.add_token(TokenKind::RightBrace),
+                },
+                '[' => self.add_token(TokenKind::LeftBracket),
+                ']' => self.add_token(TokenKind::RightBracket),
                 ',' => self.add_token(TokenKind::Comma),
+                '.' if self.reader.peek() == Some(&'.') && self.reader.peek_next() == Some('.') => {
+                    self.reader.advance();
+                    self.reader.advance();
+                    self.add_token(TokenKind::DotDotDot);
+                }
+                '.' if self.reader.peek() == Some(&'.') => {
+                    self.reader.advance();
+                    self.add_token(TokenKind::DotDot);
+                }
                 '.' => self.add_token(TokenKind::Dot),
-                '-' => self.add_token(TokenKind::Minus),
-                '+' => self.add_token(TokenKind::Plus),
+                '?' if self.reader.peek() == Some(&'?') => {
+                    self.reader.advance();
+                    self.add_token(TokenKind::QuestionQuestion);
+                }
+                '?' if self.reader.peek() == Some(&'.') => {
+                    self.reader.advance();
+                    self.add_token(TokenKind::QuestionDot);
+                }
+                '?' => self.add_token(TokenKind::QuestionMark),
                 ';' => self.add_token(TokenKind::Semicolon),
-                '*' => self.add_token(TokenKind::Star),
+                ':' => self.add_token(TokenKind::Colon),
+                '%' => self.add_token(TokenKind::Percent),
+                '&' => self.add_token(TokenKind::Ampersand),
+                '|' => self.add_token(TokenKind::Pipe),
+                '^' => self.add_token(TokenKind::Caret),
 
                 '/' => self.handleMultiCharToken(MultiCharToken::Slash),
                 '"' => self.handleMultiCharToken(MultiCharToken::String),
                 '0'..='9' => self.handleMultiCharToken(MultiCharToken::Number),
+                c if self.is_script_digit(c) => self.handleMultiCharToken(MultiCharToken::Number),
                 'a'..='z' | 'A'..='Z' | '_' => self.handleMultiCharToken(MultiCharToken::Ident),
 
+                '-' => self.handleMultiCharToken(MultiCharToken::IfDoubleIfEqualElse(
+                    '-',
+                    TokenKind::MinusMinus,
+                    TokenKind::MinusEqual,
+                    TokenKind::Minus,
+                )),
+                '+' => self.handleMultiCharToken(MultiCharToken::IfDoubleIfEqualElse(
+                    '+',
+                    TokenKind::PlusPlus,
+                    TokenKind::PlusEqual,
+                    TokenKind::Plus,
+                )),
+                '*' => self.handleMultiCharToken(MultiCharToken::IfDoubleIfEqualElse(
+                    '*',
+                    TokenKind::StarStar,
+                    TokenKind::StarEqual,
+                    TokenKind::Star,
+                )),
+                '~' => self.handleMultiCharToken(MultiCharToken::IfSlashElse(
+                    TokenKind::TildeSlash,
+                    TokenKind::Tilde,
+                )),
                 '!' => self.handleMultiCharToken(MultiCharToken::IfEqualElse(
                     TokenKind::BangEqual,
                     TokenKind::Bang,
                 )),
+                '=' if self.reader.peek() == Some(&'>') => {
+                    self.reader.advance();
+                    self.add_token(TokenKind::FatArrow);
+                }
                 '=' => self.handleMultiCharToken(MultiCharToken::IfEqualElse(
                     TokenKind::EqualEqual,
                     TokenKind::Equal,
                 )),
-                '<' => self.handleMultiCharToken(MultiCharToken::IfEqualElse(
+                '<' => self.handleMultiCharToken(MultiCharToken::IfDoubleIfEqualElse(
+                    '<',
+                    TokenKind::LessLess,
                     TokenKind::LessEqual,
                     TokenKind::Less,
                 )),
-                '>' => self.handleMultiCharToken(MultiCharToken::IfEqualElse(
+                '>' => self.handleMultiCharToken(MultiCharToken::IfDoubleIfEqualElse(
+                    '>',
+                    TokenKind::GreaterGreater,
                     TokenKind::GreaterEqual,
                     TokenKind::Greater,
                 )),
@@ -195,6 +510,19 @@ impl<'alloc> Lexer<'alloc> {
                 }
             }
         }
+        if let Some(&(from, _)) = self.interpolations.first() {
+            self.interpolations.clear();
+            self.add_error(
+                LoxError::new(
+                    self.reader.line,
+                    format!(
+                        "[Line {}]: Unterminated string interpolation",
+                        self.reader.line
+                    ),
+                )
+                .with_span(Span::new(from, self.reader.cursor)),
+            );
+        }
         self.add_token(TokenKind::Eof);
     }
 }
@@ -210,7 +538,7 @@ mod tests {
         let mut lexer = Lexer::new("(){},.-+;*!=>=<===/=><!//");
         lexer.scan_tokens();
         let tokens = lexer.tokens;
-        assert_eq!(tokens.len(), 20);
+        assert_eq!(tokens.len(), 19);
         assert_eq!(tokens[0].kind, TokenKind::LeftParen);
         assert_eq!(tokens[1].kind, TokenKind::RightParen);
         assert_eq!(tokens[2].kind, TokenKind::LeftBrace);
@@ -225,12 +553,119 @@ mod tests {
         assert_eq!(tokens[11].kind, TokenKind::GreaterEqual);
         assert_eq!(tokens[12].kind, TokenKind::LessEqual);
         assert_eq!(tokens[13].kind, TokenKind::EqualEqual);
-        assert_eq!(tokens[14].kind, TokenKind::Slash);
-        assert_eq!(tokens[15].kind, TokenKind::Equal);
-        assert_eq!(tokens[16].kind, TokenKind::Greater);
-        assert_eq!(tokens[17].kind, TokenKind::Less);
-        assert_eq!(tokens[18].kind, TokenKind::Bang);
-        assert_eq!(tokens[19].kind, TokenKind::Eof);
+        assert_eq!(tokens[14].kind, TokenKind::SlashEqual);
+        assert_eq!(tokens[15].kind, TokenKind::Greater);
+        assert_eq!(tokens[16].kind, TokenKind::Less);
+        assert_eq!(tokens[17].kind, TokenKind::Bang);
+        assert_eq!(tokens[18].kind, TokenKind::Eof);
+    }
+
+    #[test]
+    fn test_scan_compound_assignment_tokens() {
+        let mut lexer = Lexer::new("+= -= *= /= ++ -- + - * /");
+        lexer.scan_tokens();
+        let kinds: Vec<TokenKind> = lexer.tokens.iter().map(|token| token.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::PlusEqual,
+                TokenKind::MinusEqual,
+                TokenKind::StarEqual,
+                TokenKind::SlashEqual,
+                TokenKind::PlusPlus,
+                TokenKind::MinusMinus,
+                TokenKind::Plus,
+                TokenKind::Minus,
+                TokenKind::Star,
+                TokenKind::Slash,
+                TokenKind::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_arithmetic_operator_tokens() {
+        let mut lexer = Lexer::new("% ** ~/ *");
+        lexer.scan_tokens();
+        let kinds: Vec<TokenKind> = lexer.tokens.iter().map(|token| token.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Percent,
+                TokenKind::StarStar,
+                TokenKind::TildeSlash,
+                TokenKind::Star,
+                TokenKind::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_bitwise_operator_tokens() {
+        let mut lexer = Lexer::new("& | ^ ~ << >> < > ~/");
+        lexer.scan_tokens();
+        let kinds: Vec<TokenKind> = lexer.tokens.iter().map(|token| token.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Ampersand,
+                TokenKind::Pipe,
+                TokenKind::Caret,
+                TokenKind::Tilde,
+                TokenKind::LessLess,
+                TokenKind::GreaterGreater,
+                TokenKind::Less,
+                TokenKind::Greater,
+                TokenKind::TildeSlash,
+                TokenKind::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_comments() {
+        let source = "a /* one /* two */ still one */ b // line\n/// doc\n//// not doc\nc";
+        let mut lexer = Lexer::new(source);
+        lexer.scan_tokens();
+        assert!(!lexer.has_errors());
+        let lexemes: Vec<&str> = lexer
+            .tokens
+            .iter()
+            .map(|token| &source[token.from..token.to])
+            .collect();
+        assert_eq!(lexemes, vec!["a", "b", "c", ""]);
+        assert_eq!(lexer.doc_comments.len(), 1);
+        let doc = lexer.doc_comments[0];
+        assert_eq!(&source[doc.from..doc.to], "/// doc");
+        assert_eq!(doc.line, 2);
+    }
+
+    #[test]
+    fn test_scan_unterminated_block_comment() {
+        let mut lexer = Lexer::new("a\n  /* outer /* inner */");
+        lexer.scan_tokens();
+        assert_eq!(lexer.errors.len(), 1);
+        assert_eq!(lexer.errors[0].line, 2);
+        assert_eq!(lexer.errors[0].span, Some(Span::new(4, 6)));
+    }
+
+    #[test]
+    fn test_scan_dots() {
+        let mut lexer = Lexer::new(". ... .. 0..10");
+        lexer.scan_tokens();
+        let kinds: Vec<TokenKind> = lexer.tokens.iter().map(|token| token.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Dot,
+                TokenKind::DotDotDot,
+                TokenKind::DotDot,
+                TokenKind::Number,
+                TokenKind::DotDot,
+                TokenKind::Number,
+                TokenKind::Eof,
+            ]
+        );
     }
 
     #[test]
@@ -243,6 +678,128 @@ mod tests {
         assert_eq!(tokens[0].kind, TokenKind::String);
     }
 
+    #[test]
+    fn test_scan_string_with_escapes() {
+        let mut lexer = Lexer::new(r#""say \"hi\"\n" "bad \q""#);
+        lexer.scan_tokens();
+        assert_eq!(lexer.tokens[0].kind, TokenKind::String);
+        assert_eq!((lexer.tokens[0].from, lexer.tokens[0].to), (0, 14));
+        assert_eq!(
+            lexer.decoded.get(&0).map(String::as_str),
+            Some("say \"hi\"\n")
+        );
+        assert_eq!(lexer.errors.len(), 1);
+        assert_eq!(lexer.errors[0].span, Some(Span::new(20, 22)));
+    }
+
+    #[test]
+    fn test_scan_interpolated_string() {
+        let source = r#""Hi ${name}, ${ {"${x}"} } \${y}""#;
+        let mut lexer = Lexer::new(source);
+        lexer.scan_tokens();
+        assert!(!lexer.has_errors());
+        let lexemes: Vec<(TokenKind, &str)> = lexer
+            .tokens
+            .iter()
+            .map(|token| (token.kind, &source[token.from..token.to]))
+            .collect();
+        assert_eq!(
+            lexemes,
+            vec![
+                (TokenKind::InterpolationStart, "\"Hi ${"),
+                (TokenKind::Identifier, "name"),
+                (TokenKind::InterpolationMiddle, "}, ${"),
+                (TokenKind::LeftBrace, "{"),
+                (TokenKind::InterpolationStart, "\"${"),
+                (TokenKind::Identifier, "x"),
+                (TokenKind::InterpolationEnd, "}\""),
+                (TokenKind::RightBrace, "}"),
+                (TokenKind::InterpolationEnd, "} \\${y}\""),
+                (TokenKind::Eof, ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_unterminated_interpolation() {
+        let mut lexer = Lexer::new("\"a ${b");
+        lexer.scan_tokens();
+        assert!(lexer.has_errors());
+        assert_eq!(lexer.errors[0].span, Some(Span::new(3, 6)));
+    }
+
+    #[test]
+    fn test_scan_numbers() {
+        let source = "1 2.5 1e-3 6.02E23 0xFF 0b1010 1_000_000";
+        let mut lexer = Lexer::new(source);
+        lexer.scan_tokens();
+        assert!(!lexer.has_errors());
+        let lexemes: Vec<&str> = lexer
+            .tokens
+            .iter()
+            .filter(|token| token.kind == TokenKind::Number)
+            .map(|token| &source[token.from..token.to])
+            .collect();
+        assert_eq!(
+            lexemes,
+            vec!["1", "2.5", "1e-3", "6.02E23", "0xFF", "0b1010", "1_000_000"]
+        );
+
+        // There are no octal literals, a leading zero is just decimal
+        let mut lexer = Lexer::new("08 007.5");
+        lexer.scan_tokens();
+        assert!(!lexer.has_errors());
+        assert_eq!(lexer.tokens[0].kind, TokenKind::Number);
+        assert_eq!((lexer.tokens[1].from, lexer.tokens[1].to), (3, 8));
+    }
+
+    #[test]
+    fn test_scan_script_digits() {
+        let source = "१२३ + ३.५e२";
+        let mut lexer = Lexer::new(source);
+        lexer.scan_tokens();
+        assert!(!lexer.has_errors());
+        let lexemes: Vec<(TokenKind, &str)> = lexer
+            .tokens
+            .iter()
+            .map(|token| (token.kind, &source[token.from..token.to]))
+            .collect();
+        assert_eq!(
+            lexemes,
+            vec![
+                (TokenKind::Number, "१२३"),
+                (TokenKind::Plus, "+"),
+                (TokenKind::Number, "३.५e२"),
+                (TokenKind::Eof, ""),
+            ]
+        );
+
+        let mut lexer = Lexer::new(source).with_numeral_scripts(vec![]);
+        lexer.scan_tokens();
+        assert!(lexer.has_errors());
+    }
+
+    #[test]
+    fn test_scan_malformed_numbers() {
+        for (source, span) in [
+            ("1.", Span::new(0, 2)),
+            ("0x", Span::new(0, 2)),
+            ("0b", Span::new(0, 2)),
+            ("1e+", Span::new(0, 3)),
+            ("1__0", Span::new(0, 3)),
+            ("1_", Span::new(0, 2)),
+            ("0b102", Span::new(0, 5)),
+            ("0b2", Span::new(0, 3)),
+            ("0xg1", Span::new(0, 4)),
+            ("12px", Span::new(0, 4)),
+        ] {
+            let mut lexer = Lexer::new(source);
+            lexer.scan_tokens();
+            assert_eq!(lexer.errors.len(), 1, "{}", source);
+            assert_eq!(lexer.errors[0].span, Some(span), "{}", source);
+        }
+    }
+
     #[test]
     fn test_scan_arbitrary_source() {
         let source = "-2 true false nil \"Hello\"";
@@ -279,4 +836,41 @@ mod tests {
         assert!(output.contains("[Line 1]: Unexpected character: '$'"));
         assert!(output.contains("[Line 1]: Unterminated string")); */
     }
+
+    #[test]
+    fn test_remapped_keywords() {
+        let mut lexer = Lexer::new_with_keywords("sthir x = 1;", Some(r#"{"sthir": "Const"}"#));
+        lexer.scan_tokens();
+        let kinds: Vec<TokenKind> = lexer.tokens.iter().map(|token| token.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Const,
+                TokenKind::Identifier,
+                TokenKind::Equal,
+                TokenKind::Number,
+                TokenKind::Semicolon,
+                TokenKind::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_question_tokens() {
+        let mut lexer = Lexer::new("a ?? b?.c ?");
+        lexer.scan_tokens();
+        let kinds: Vec<TokenKind> = lexer.tokens.iter().map(|token| token.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Identifier,
+                TokenKind::QuestionQuestion,
+                TokenKind::Identifier,
+                TokenKind::QuestionDot,
+                TokenKind::Identifier,
+                TokenKind::QuestionMark,
+                TokenKind::Eof,
+            ]
+        );
+    }
 }
@@ -0,0 +1,107 @@
+use serde::Deserialize;
+
+/// Scripts whose decimal digits may be used in number literals alongside the
+/// ASCII digits, e.g. `१२३` in Devanagari.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum NumeralScript {
+    ArabicIndic,
+    Bengali,
+    Devanagari,
+}
+
+impl NumeralScript {
+    pub const ALL: [NumeralScript; 3] = [
+        NumeralScript::ArabicIndic,
+        NumeralScript::Bengali,
+        NumeralScript::Devanagari,
+    ];
+
+    fn zero(self) -> char {
+        match self {
+            NumeralScript::ArabicIndic => '\u{0660}',
+            NumeralScript::Bengali => '\u{09E6}',
+            NumeralScript::Devanagari => '\u{0966}',
+        }
+    }
+
+    /// The value of `c` if it is a decimal digit of this script
+    pub fn digit_value(self, c: char) -> Option<u32> {
+        let value = (c as u32).wrapping_sub(self.zero() as u32);
+        (value < 10).then_some(value)
+    }
+}
+
+/// The scripts accepted by a lexer unless configured otherwise
+pub fn default_numeral_scripts() -> Vec<NumeralScript> {
+    vec![NumeralScript::Devanagari]
+}
+
+/// Replace the digits of every known script with their ASCII equivalent
+fn normalise_digit(c: char) -> char {
+    NumeralScript::ALL
+        .iter()
+        .find_map(|script| script.digit_value(c))
+        .and_then(|value| char::from_digit(value, 10))
+        .unwrap_or(c)
+}
+
+/// Compute the value of a number literal that the lexer has already
+/// validated, e.g. `1_000`, `2.5e-3`, `0xFF`, `0b1010` or `१२३`.
+///
+/// Returns `None` if `raw` isn't a well formed literal.
+pub fn parse_number(raw: &str) -> Option<f64> {
+    let digits: String = raw
+        .chars()
+        .filter(|&c| c != '_')
+        .map(normalise_digit)
+        .collect();
+
+    let radix_digits = match digits.get(..2) {
+        Some("0x") | Some("0X") => Some((16, &digits[2..])),
+        Some("0b") | Some("0B") => Some((2, &digits[2..])),
+        _ => None,
+    };
+
+    match radix_digits {
+        // Accumulate into a float so that long literals lose precision
+        // instead of overflowing
+        Some((radix, digits)) if !digits.is_empty() => digits.chars().try_fold(0.0, |value, c| {
+            c.to_digit(radix)
+                .map(|digit| value * radix as f64 + digit as f64)
+        }),
+        Some(_) => None,
+        None => digits.parse::<f64>().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("42"), Some(42.0));
+        assert_eq!(parse_number("1_000_000"), Some(1_000_000.0));
+        assert_eq!(parse_number("2.5"), Some(2.5));
+        assert_eq!(parse_number("1e-3"), Some(0.001));
+        assert_eq!(parse_number("1E3"), Some(1000.0));
+        assert_eq!(parse_number("0xFF"), Some(255.0));
+        assert_eq!(parse_number("0b1010"), Some(10.0));
+        assert_eq!(parse_number("08"), Some(8.0));
+    }
+
+    #[test]
+    fn test_parse_number_with_script_digits() {
+        assert_eq!(parse_number("१२३"), Some(123.0));
+        assert_eq!(parse_number("३.५"), Some(3.5));
+        assert_eq!(parse_number("১০"), Some(10.0));
+        assert_eq!(parse_number("٤٢"), Some(42.0));
+    }
+
+    #[test]
+    fn test_parse_malformed_number() {
+        assert_eq!(parse_number("0x"), None);
+        assert_eq!(parse_number("0b2"), None);
+        assert_eq!(parse_number("1e"), None);
+    }
+}
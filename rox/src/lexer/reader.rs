@@ -10,6 +10,7 @@ pub struct Reader<'de> {
     pub start: usize,
 
     // Cursor is always at the next character to be read
+    // Both start and cursor are byte offsets into the source
     pub cursor: usize,
 
     pub line: usize,
@@ -30,6 +31,13 @@ impl<'de> Reader<'de> {
         self.chars.peek()
     }
 
+    /// The character after the one returned by `peek`
+    pub fn peek_next(&self) -> Option<char> {
+        let mut chars = self.chars.clone();
+        chars.next();
+        chars.next()
+    }
+
     pub fn advance(&mut self) -> Option<char> {
         if self.cursor >= self.source.len() {
             return None;
@@ -38,7 +46,7 @@ impl<'de> Reader<'de> {
         let c = self.chars.next();
 
         if let Some(c) = c {
-            self.cursor += 1;
+            self.cursor += c.len_utf8();
 
             if c == '\n' {
                 self.line += 1;
@@ -97,4 +105,12 @@ mod test {
         reader.advance_while(|c| c.is_numeric());
         assert_eq!(reader.cursor, 3);
     }
+
+    #[test]
+    fn test_reader_cursor_is_byte_offset() {
+        let source = "\"नमस्ते\"";
+        let mut reader = Reader::new(source);
+        while reader.advance().is_some() {}
+        assert_eq!(reader.cursor, source.len());
+    }
 }
@@ -7,15 +7,22 @@ pub enum TokenKind {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Comma,
     Dot,
     Minus,
     Plus,
     Semicolon,
     Colon,
+    Percent,
     Slash,
     Star,
     QuestionMark,
+    Ampersand,
+    Pipe,
+    Caret,
+    Tilde,
 
     // One Or Two Character Tokens
     Bang,
@@ -24,31 +31,65 @@ pub enum TokenKind {
     EqualEqual,
     Greater,
     GreaterEqual,
+    GreaterGreater,
     Less,
     LessEqual,
+    LessLess,
+    FatArrow,
+    DotDot,
+    DotDotDot,
+    MinusEqual,
+    MinusMinus,
+    PlusEqual,
+    PlusPlus,
+    QuestionDot,
+    QuestionQuestion,
+    SlashEqual,
+    StarEqual,
+    StarStar,
+    TildeSlash,
 
     // Literals
     Identifier,
     String,
     Number,
 
+    // Interpolated strings, e.g. `"a ${b} c ${d} e"` is lexed as
+    // InterpolationStart(`"a ${`) b InterpolationMiddle(`} c ${`) d InterpolationEnd(`} e"`)
+    InterpolationStart,
+    InterpolationMiddle,
+    InterpolationEnd,
+
+    // `///` comments, kept out of the token stream in `Lexer::doc_comments`
+    DocComment,
+
     // Keywords
     And,
+    Catch,
     Class,
+    Const,
     Else,
+    Enum,
     False,
+    Finally,
     Fun,
     For,
     If,
+    In,
+    Let,
+    Match,
     Nil,
     Or,
     Print,
     Return,
     Super,
     This,
+    Throw,
     True,
+    Try,
     Var,
     While,
+    Yield,
 
     // EOF
     Eof,
@@ -1,15 +1,35 @@
 mod ast;
 mod lexer;
 mod parser;
+mod resolver;
 
 pub mod lox_error;
+pub mod options;
 
+use lox_error::LoxError;
+use options::LanguageOptions;
 use wasm_bindgen::prelude::*;
 
+/// `options` is a `LanguageOptions` object, or `undefined` for the defaults.
+/// Invalid options are returned as errors, like parse errors.
 #[wasm_bindgen]
-pub fn parse_for_js(source: &str) -> JsValue {
+pub fn parse_for_js(source: &str, options: JsValue) -> JsValue {
+    let options: LanguageOptions = if options.is_undefined() || options.is_null() {
+        LanguageOptions::default()
+    } else {
+        match serde_wasm_bindgen::from_value(options) {
+            Ok(options) => options,
+            Err(error) => {
+                let errors = vec![LoxError::new(
+                    0,
+                    format!("Invalid language options: {}", error),
+                )];
+                return serde_wasm_bindgen::to_value(&errors).unwrap();
+            }
+        }
+    };
     let allocator = bumpalo::Bump::new();
-    let mut parser = parser::Parser::new(source, &allocator);
+    let mut parser = parser::Parser::new(source, &allocator).with_options(options);
     let result = parser.parse();
     match result {
         Ok(ast) => serde_wasm_bindgen::to_value(&ast).unwrap(),
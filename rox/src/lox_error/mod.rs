@@ -1,3 +1,4 @@
+use crate::ast::span::Span;
 use serde::Serialize;
 use std::fmt::Display;
 
@@ -5,11 +6,38 @@ use std::fmt::Display;
 pub struct LoxError {
     pub message: String,
     pub line: usize,
+    pub span: Option<Span>,
+    /// Other places in the source that help explain the error
+    pub labels: Vec<Label>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
 }
 
 impl LoxError {
     pub fn new(line: usize, message: String) -> Self {
-        Self { message, line }
+        Self {
+            message,
+            line,
+            span: None,
+            labels: Vec::new(),
+        }
+    }
+
+    /// Point the error at the offending part of the source.
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    /// Point at a related part of the source, like the declaration a bad
+    /// assignment refers to.
+    pub fn with_label(mut self, span: Span, message: String) -> Self {
+        self.labels.push(Label { span, message });
+        self
     }
 }
 
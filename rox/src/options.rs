@@ -0,0 +1,46 @@
+use crate::lexer::number::{default_numeral_scripts, NumeralScript};
+use serde::Deserialize;
+
+/// Switches for language behaviour that differs between the dialects we teach.
+/// Any option left out keeps its default, unknown options are rejected.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LanguageOptions {
+    pub var_scope: VarScope,
+    /// Scripts, besides ASCII, whose digits are accepted in number literals
+    pub numeral_scripts: Vec<NumeralScript>,
+}
+
+impl Default for LanguageOptions {
+    fn default() -> Self {
+        Self {
+            var_scope: VarScope::default(),
+            numeral_scripts: default_numeral_scripts(),
+        }
+    }
+}
+
+/// Where a `var` declaration is visible. `let` and `const` are always
+/// block-scoped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum VarScope {
+    /// Visible from the declaration to the end of the enclosing block, as in Lox
+    #[default]
+    Block,
+    /// Hoisted to the top of the enclosing function, as in JavaScript
+    Function,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_options() {
+        let options: LanguageOptions =
+            serde_json::from_str(r#"{"var_scope": "Function"}"#).unwrap();
+        assert_eq!(options.var_scope, VarScope::Function);
+        assert!(serde_json::from_str::<LanguageOptions>(r#"{"varScope": "Function"}"#).is_err());
+        assert!(serde_json::from_str::<LanguageOptions>(r#"{"var_scope": "function"}"#).is_err());
+    }
+}
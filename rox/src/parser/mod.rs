@@ -1,43 +1,72 @@
 use crate::{
     ast::{
         expression::{
-            Assignment, Binary, BooleanLiteral, Call, Expression, Grouping, Literal, LiteralValue,
-            Logical, NilLiteral, NumberLiteral, StringLiteral, Unary, Variable,
+            Assignment, Binary, BooleanLiteral, Call, CompoundAssignment, Expression, Get,
+            Grouping, Index, Interpolation, InterpolationPart, Lambda, List, Literal, LiteralValue,
+            Logical, Map, MapEntry, NilLiteral, NumberLiteral, Range, StringLiteral, Super, This,
+            Unary, Update, Variable, Yield,
         },
         operator::Operator,
+        pattern::{
+            BindingPattern, ListPattern, LiteralPattern, MapPattern, MapPatternEntry, Pattern,
+            VariantPattern, WildcardPattern,
+        },
         span::Span,
         statement::{
-            Block, Declaration, Expression as ExpressionStatement, For, Function, If, Print,
-            Return, Statement, While,
+            Block, Catch, Class, Declaration, DeclarationKind, Enum, EnumVariant,
+            Expression as ExpressionStatement, For, ForIn, Function, If, Match, MatchArm, Method,
+            MethodKind, Parameter, Print, Return, Statement, Throw, Try, While,
         },
         Ast,
     },
-    lexer::{token::Token, token_kind::TokenKind, Lexer},
+    lexer::{number::parse_number, token::Token, token_kind::TokenKind, Lexer},
     lox_error::LoxError,
+    options::LanguageOptions,
+    resolver::Resolver,
 };
 use bumpalo::{boxed::Box, collections::Vec as BumpVec, Bump};
 
+/// Where a `yield` would end up, one per function being parsed
+enum YieldContext {
+    /// The parameter list, where a default value can't yield
+    Parameters,
+    /// The body, set once a `yield` is found directly in it
+    Body(bool),
+}
+
 pub struct Parser<'alloc> {
     allocator: &'alloc Bump,
     source: &'alloc str,
     lexer: Lexer<'alloc>,
     cursor: usize,
     errors: Vec<LoxError>,
+    options: LanguageOptions,
+    /// One entry per function being parsed, innermost last
+    yields: Vec<YieldContext>,
 }
 
 impl<'alloc> Parser<'alloc> {
     pub fn new(source: &'alloc str, allocator: &'alloc Bump) -> Self {
-        let mut lexer = Lexer::new(source);
-        lexer.scan_tokens();
         Self {
             allocator,
             source,
-            lexer,
+            lexer: Lexer::new(source),
             cursor: 0,
             errors: Vec::new(),
+            options: LanguageOptions::default(),
+            yields: Vec::new(),
         }
     }
 
+    /// Set the language options the program is checked against
+    pub fn with_options(mut self, options: LanguageOptions) -> Self {
+        self.lexer = self
+            .lexer
+            .with_numeral_scripts(options.numeral_scripts.clone());
+        self.options = options;
+        self
+    }
+
     fn alloc<T>(&self, x: T) -> Box<'alloc, T> {
         Box::new_in(x, self.allocator)
     }
@@ -66,7 +95,13 @@ impl<'alloc> Parser<'alloc> {
             return Err(self.errors.clone());
         }
 
-        Ok(Ast::new(Span::new(0, self.source.len() - 1), body))
+        let ast = Ast::new(Span::new(0, self.source.len() - 1), body);
+        let errors = Resolver::new(self.source, self.options.clone()).resolve(&ast);
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(ast)
     }
 
     fn synchronize(&mut self) {
@@ -77,13 +112,19 @@ impl<'alloc> Parser<'alloc> {
             }
             match self.curr_token_kind() {
                 TokenKind::Class
+                | TokenKind::Const
+                | TokenKind::Enum
                 | TokenKind::Fun
+                | TokenKind::Let
                 | TokenKind::Var
                 | TokenKind::For
                 | TokenKind::If
+                | TokenKind::Match
                 | TokenKind::While
                 | TokenKind::Print
-                | TokenKind::Return => {
+                | TokenKind::Return
+                | TokenKind::Throw
+                | TokenKind::Try => {
                     return;
                 }
                 _ => {
@@ -100,6 +141,18 @@ impl<'alloc> Parser<'alloc> {
         self.curr_token().kind
     }
 
+    fn next_token_kind(&self) -> TokenKind {
+        self.nth_token_kind(1)
+    }
+
+    /// Kind of the token `n` tokens after the current one
+    fn nth_token_kind(&self, n: usize) -> TokenKind {
+        self.lexer
+            .tokens
+            .get(self.cursor + n)
+            .map_or(TokenKind::Eof, |token| token.kind)
+    }
+
     fn consume(&mut self, token: Token) {
         if self.curr_token_kind() == token.kind {
             self.cursor += 1;
@@ -135,10 +188,14 @@ impl<'alloc> Parser<'alloc> {
             TokenKind::Nil => Ok(LiteralValue::Nil(NilLiteral { span })),
             TokenKind::String => Ok(LiteralValue::String(StringLiteral {
                 span,
-                value: lexeme,
+                raw: lexeme,
+                value: self.decoded_string(token, Span::new(token.from + 1, token.to - 1)),
             })),
             TokenKind::Number => {
-                let value = lexeme.parse::<f64>().unwrap();
+                let value = parse_number(lexeme).ok_or_else(|| {
+                    LoxError::new(token.line, format!("Invalid number literal '{}'", lexeme))
+                        .with_span(span)
+                })?;
                 Ok(LiteralValue::Number(NumberLiteral {
                     span,
                     raw: lexeme,
@@ -152,22 +209,89 @@ impl<'alloc> Parser<'alloc> {
         }
     }
 
+    /// The value of `contents`, a span inside `token`, as decoded by the
+    /// lexer, or the source text itself when it has no escapes
+    fn decoded_string(&self, token: Token, contents: Span) -> &'alloc str {
+        match self.lexer.decoded.get(&token.from) {
+            Some(value) => self.allocator.alloc_str(value),
+            None => &self.source[contents.from..contents.to],
+        }
+    }
+
+    /// A string segment of an interpolated string, without its delimiters
+    fn interpolation_part(
+        &self,
+        token: Token,
+        delimiter_len: usize,
+    ) -> Option<InterpolationPart<'alloc>> {
+        let span = Span::new(token.from + 1, token.to - delimiter_len);
+        if span.from == span.to {
+            return None;
+        }
+        Some(InterpolationPart::String(StringLiteral {
+            span,
+            raw: &self.source[span.from..span.to],
+            value: self.decoded_string(token, span),
+        }))
+    }
+
+    /// The `///` comments between the previous token and the current one,
+    /// without their slashes and joined into a single string
+    fn doc_comment(&self) -> Option<&'alloc str> {
+        let from = match self.cursor {
+            0 => 0,
+            cursor => self.lexer.tokens[cursor - 1].to,
+        };
+        let to = self.curr_token().from;
+        let lines: Vec<&str> = self
+            .lexer
+            .doc_comments
+            .iter()
+            .filter(|comment| comment.from >= from && comment.to <= to)
+            .map(|comment| {
+                let text = self.source[comment.from + 3..comment.to].trim_end();
+                text.strip_prefix(' ').unwrap_or(text)
+            })
+            .collect();
+        if lines.is_empty() {
+            None
+        } else {
+            Some(self.allocator.alloc_str(&lines.join("\n")))
+        }
+    }
+
     fn operator(&self) -> Result<Operator, LoxError> {
         let span = Span::new(self.curr_token().from, self.curr_token().to);
         match self.curr_token_kind() {
+            TokenKind::Ampersand => Ok(Operator::Ampersand(span)),
             TokenKind::And => Ok(Operator::And(span)),
             TokenKind::Bang => Ok(Operator::Bang(span)),
             TokenKind::BangEqual => Ok(Operator::BangEqual(span)),
+            TokenKind::Caret => Ok(Operator::Caret(span)),
             TokenKind::EqualEqual => Ok(Operator::EqualEqual(span)),
             TokenKind::Greater => Ok(Operator::Greater(span)),
             TokenKind::GreaterEqual => Ok(Operator::GreaterEqual(span)),
+            TokenKind::GreaterGreater => Ok(Operator::GreaterGreater(span)),
             TokenKind::Less => Ok(Operator::Less(span)),
             TokenKind::LessEqual => Ok(Operator::LessEqual(span)),
+            TokenKind::LessLess => Ok(Operator::LessLess(span)),
             TokenKind::Minus => Ok(Operator::Minus(span)),
+            TokenKind::MinusEqual => Ok(Operator::MinusEqual(span)),
+            TokenKind::MinusMinus => Ok(Operator::MinusMinus(span)),
             TokenKind::Or => Ok(Operator::Or(span)),
+            TokenKind::Percent => Ok(Operator::Percent(span)),
+            TokenKind::Pipe => Ok(Operator::Pipe(span)),
             TokenKind::Plus => Ok(Operator::Plus(span)),
+            TokenKind::PlusEqual => Ok(Operator::PlusEqual(span)),
+            TokenKind::PlusPlus => Ok(Operator::PlusPlus(span)),
+            TokenKind::QuestionQuestion => Ok(Operator::QuestionQuestion(span)),
             TokenKind::Slash => Ok(Operator::Slash(span)),
+            TokenKind::SlashEqual => Ok(Operator::SlashEqual(span)),
             TokenKind::Star => Ok(Operator::Star(span)),
+            TokenKind::StarEqual => Ok(Operator::StarEqual(span)),
+            TokenKind::StarStar => Ok(Operator::StarStar(span)),
+            TokenKind::Tilde => Ok(Operator::Tilde(span)),
+            TokenKind::TildeSlash => Ok(Operator::TildeSlash(span)),
             _ => Err(LoxError::new(
                 self.curr_token().line,
                 format!("Expected operator but got {:?}", self.curr_token_kind()),
@@ -205,19 +329,70 @@ impl<'alloc> Parser<'alloc> {
 
     fn parse_statement(&mut self) -> Result<Statement<'alloc>, LoxError> {
         match self.curr_token_kind() {
-            TokenKind::Fun => self.parse_function_declaration(),
-            TokenKind::Var => self.parse_variable_declaration(),
+            // `fun (` starts a lambda in an expression statement
+            TokenKind::Fun if self.next_token_kind() == TokenKind::Identifier => {
+                self.parse_function_declaration()
+            }
+            TokenKind::Var | TokenKind::Let | TokenKind::Const => self.parse_variable_declaration(),
             TokenKind::While => self.parse_while_statement(),
             TokenKind::For => self.parse_for_statement(),
             TokenKind::If => self.parse_if_statement(),
+            TokenKind::Match => self.parse_match_statement(),
+            TokenKind::Enum => self.parse_enum_declaration(),
+            TokenKind::Class => self.parse_class_declaration(),
             TokenKind::Print => self.parse_print_statement(),
             TokenKind::Return => self.parse_return_statement(),
-            TokenKind::LeftBrace => self.parse_block_statement(),
+            TokenKind::Throw => self.parse_throw_statement(),
+            TokenKind::Try => self.parse_try_statement(),
+            TokenKind::LeftBrace if !self.is_map_statement() => self.parse_block_statement(),
             _ => self.parse_expression_statement(),
         }
     }
 
+    /// Whether the `{` at the start of a statement opens a map rather than a
+    /// block, i.e. `{"a": 1}.size;` or `{} == x;`. A block can't start with
+    /// `key:`, and an empty block can't be followed by an operator. `{};`
+    /// is left a block so `if (x) {};` keeps its empty body.
+    fn is_map_statement(&self) -> bool {
+        match self.next_token_kind() {
+            TokenKind::String
+            | TokenKind::Number
+            | TokenKind::Identifier
+            | TokenKind::True
+            | TokenKind::False => self.nth_token_kind(2) == TokenKind::Colon,
+            TokenKind::RightBrace => matches!(
+                self.nth_token_kind(2),
+                TokenKind::Dot
+                    | TokenKind::QuestionDot
+                    | TokenKind::QuestionMark
+                    | TokenKind::QuestionQuestion
+                    | TokenKind::Plus
+                    | TokenKind::Star
+                    | TokenKind::StarStar
+                    | TokenKind::Slash
+                    | TokenKind::TildeSlash
+                    | TokenKind::Percent
+                    | TokenKind::EqualEqual
+                    | TokenKind::BangEqual
+                    | TokenKind::Greater
+                    | TokenKind::GreaterEqual
+                    | TokenKind::GreaterGreater
+                    | TokenKind::Less
+                    | TokenKind::LessEqual
+                    | TokenKind::LessLess
+                    | TokenKind::Ampersand
+                    | TokenKind::Pipe
+                    | TokenKind::Caret
+                    | TokenKind::DotDot
+                    | TokenKind::And
+                    | TokenKind::Or
+            ),
+            _ => false,
+        }
+    }
+
     fn parse_function_declaration(&mut self) -> Result<Statement<'alloc>, LoxError> {
+        let doc = self.doc_comment();
         let fun_keyword = self.eat(TokenKind::Fun)?;
         if self.curr_token_kind() != TokenKind::Identifier {
             return Err(LoxError::new(
@@ -229,12 +404,106 @@ impl<'alloc> Parser<'alloc> {
             ));
         }
         let name = self.eat(TokenKind::Identifier)?;
+        let (params, body, is_generator) = self.parse_function_params_and_body()?;
+        Ok(Statement::Function(Box::new_in(
+            Function {
+                span: Span::new(fun_keyword.from, body.span().to),
+                doc,
+                name,
+                params,
+                body,
+                is_generator,
+            },
+            &self.allocator,
+        )))
+    }
+
+    /// `enum Name { A, B, }`, duplicate variants are reported but still parsed
+    fn parse_enum_declaration(&mut self) -> Result<Statement<'alloc>, LoxError> {
+        let doc = self.doc_comment();
+        let enum_keyword = self.eat(TokenKind::Enum)?;
+        let name = self.eat(TokenKind::Identifier)?;
+        self.eat(TokenKind::LeftBrace)?;
+        let mut variants: Vec<EnumVariant<'alloc>> = Vec::new();
+
+        // Variants are separated by commas, a trailing comma is allowed
+        while self.curr_token_kind() != TokenKind::RightBrace {
+            let variant_token = self.eat(TokenKind::Identifier)?;
+            let variant = EnumVariant {
+                span: Span::new(variant_token.from, variant_token.to),
+                name: &self.source[variant_token.from..variant_token.to],
+            };
+            // Reported without bailing out so every duplicate is found
+            if let Some(first) = variants.iter().find(|first| first.name == variant.name) {
+                self.add_error(
+                    LoxError::new(
+                        variant_token.line,
+                        format!(
+                            "Syntax Error: Duplicate variant {} in enum {}",
+                            variant.name,
+                            &self.source[name.from..name.to]
+                        ),
+                    )
+                    .with_span(variant.span)
+                    .with_label(first.span, format!("{} is first declared here", first.name)),
+                );
+            }
+            variants.push(variant);
+            if self.curr_token_kind() == TokenKind::Comma {
+                self.bump_any();
+            } else {
+                break;
+            }
+        }
+
+        let end_brace = self.eat(TokenKind::RightBrace)?;
+        Ok(Statement::Enum(self.alloc(Enum {
+            span: Span::new(enum_keyword.from, end_brace.to),
+            doc,
+            name,
+            variants,
+        })))
+    }
+
+    /// The `(params) { body }` shared by function declarations and lambdas
+    /// Also reports whether the body makes the function a generator
+    fn parse_function_params_and_body(
+        &mut self,
+    ) -> Result<(Vec<Parameter<'alloc>>, Statement<'alloc>, bool), LoxError> {
         self.eat(TokenKind::LeftParen)?;
-        let mut params = Vec::new();
+        self.yields.push(YieldContext::Parameters);
+        let params = self.parse_parameters();
+        self.yields.pop();
+        let params = params?;
+        self.eat(TokenKind::RightParen)?;
+        let (body, is_generator) = self.parse_function_body()?;
+        Ok((params, body, is_generator))
+    }
+
+    /// The comma separated parameters up to the closing `)`
+    fn parse_parameters(&mut self) -> Result<Vec<Parameter<'alloc>>, LoxError> {
+        let mut params: Vec<Parameter<'alloc>> = Vec::new();
 
         if self.curr_token_kind() != TokenKind::RightParen {
             loop {
-                let param = self.eat(TokenKind::Identifier)?;
+                let param = self.parse_parameter()?;
+                if let Some(previous) = params.last() {
+                    if previous.rest {
+                        return Err(LoxError::new(
+                            param.name.line,
+                            "Syntax Error: Rest parameter must be the last parameter".to_string(),
+                        )
+                        .with_span(previous.span));
+                    }
+                    if previous.default.is_some() && param.default.is_none() && !param.rest {
+                        return Err(LoxError::new(
+                            param.name.line,
+                            "Syntax Error: Required parameter can't follow a parameter with a default value"
+                                .to_string(),
+                        )
+                        .with_span(param.span));
+                    }
+                }
                 params.push(param);
                 if self.curr_token_kind() == TokenKind::Comma {
                     self.bump_any();
@@ -243,45 +512,220 @@ impl<'alloc> Parser<'alloc> {
                 }
             }
         }
-        self.eat(TokenKind::RightParen)?;
-        let body = self.parse_block_statement()?;
-        Ok(Statement::Function(Box::new_in(
-            Function {
-                span: Span::new(fun_keyword.from, body.span().to),
-                name,
-                params,
-                body,
-            },
-            &self.allocator,
-        )))
+        Ok(params)
     }
 
-    fn parse_variable_declaration(&mut self) -> Result<Statement<'alloc>, LoxError> {
-        let var_keyword = self.eat(TokenKind::Var)?;
+    /// The `{ body }` of a function, method or getter, and whether a
+    /// `yield` in it makes the function a generator
+    fn parse_function_body(&mut self) -> Result<(Statement<'alloc>, bool), LoxError> {
+        self.yields.push(YieldContext::Body(false));
+        let body = self.parse_block_statement();
+        let is_generator = matches!(self.yields.pop(), Some(YieldContext::Body(true)));
+        Ok((body?, is_generator))
+    }
+
+    fn parse_class_declaration(&mut self) -> Result<Statement<'alloc>, LoxError> {
+        let doc = self.doc_comment();
+        let class_keyword = self.eat(TokenKind::Class)?;
+        let name = self.eat(TokenKind::Identifier)?;
+        let superclass = if self.curr_token_kind() == TokenKind::Less {
+            self.bump_any();
+            Some(self.eat(TokenKind::Identifier)?)
+        } else {
+            None
+        };
+
+        self.eat(TokenKind::LeftBrace)?;
+        let mut methods = Vec::new();
+        while !matches!(
+            self.curr_token_kind(),
+            TokenKind::RightBrace | TokenKind::Eof
+        ) {
+            methods.push(self.parse_method()?);
+        }
+        let end_brace = self.eat(TokenKind::RightBrace)?;
+
+        Ok(Statement::Class(self.alloc(Class {
+            span: Span::new(class_keyword.from, end_brace.to),
+            doc,
+            name,
+            superclass,
+            methods,
+        })))
+    }
+
+    fn parse_method(&mut self) -> Result<Method<'alloc>, LoxError> {
+        let start = self.curr_token();
+        let kind = match start.kind {
+            TokenKind::Class => {
+                self.bump_any();
+                MethodKind::Static
+            }
+            // `set` is only special in front of a method name, so it can
+            // still name a method or a variable
+            TokenKind::Identifier
+                if self.curr_token_lexeme() == "set"
+                    && self.next_token_kind() == TokenKind::Identifier =>
+            {
+                self.bump_any();
+                MethodKind::Setter
+            }
+            _ if self.next_token_kind() == TokenKind::LeftBrace => MethodKind::Getter,
+            _ if self.curr_token_lexeme() == "init" => MethodKind::Initializer,
+            _ => MethodKind::Method,
+        };
+        let name = self.eat(TokenKind::Identifier)?;
+
+        let (params, body, is_generator) = if let MethodKind::Getter = kind {
+            let (body, is_generator) = self.parse_function_body()?;
+            (Vec::new(), body, is_generator)
+        } else {
+            self.parse_function_params_and_body()?
+        };
+        if kind == MethodKind::Setter && (params.len() != 1 || params[0].rest) {
+            return Err(LoxError::new(
+                name.line,
+                format!(
+                    "Syntax Error: Setter {} must take exactly one parameter",
+                    &self.source[name.from..name.to]
+                ),
+            )
+            .with_span(Span::new(start.from, name.to)));
+        }
+
+        Ok(Method {
+            span: Span::new(start.from, body.span().to),
+            kind,
+            name,
+            params,
+            body,
+            is_generator,
+        })
+    }
+
+    fn parse_parameter(&mut self) -> Result<Parameter<'alloc>, LoxError> {
+        let start = self.curr_token();
+        let rest = start.kind == TokenKind::DotDotDot;
+        if rest {
+            self.bump_any();
+        }
         let name = self.eat(TokenKind::Identifier)?;
+        let mut span = Span::new(start.from, name.to);
+
+        let default = if self.curr_token_kind() == TokenKind::Equal {
+            if rest {
+                return Err(LoxError::new(
+                    name.line,
+                    "Syntax Error: Rest parameter can't have a default value".to_string(),
+                )
+                .with_span(span));
+            }
+            self.bump_any();
+            let default = self.parse_expression()?;
+            span = span.end(default.span().to);
+            Some(default)
+        } else {
+            None
+        };
+
+        Ok(Parameter {
+            span,
+            name,
+            default,
+            rest,
+        })
+    }
+
+    fn parse_variable_declaration(&mut self) -> Result<Statement<'alloc>, LoxError> {
+        let doc = self.doc_comment();
+        let var_keyword = self.curr_token();
+        let kind = match var_keyword.kind {
+            TokenKind::Const => DeclarationKind::Const,
+            TokenKind::Let => DeclarationKind::Let,
+            _ => DeclarationKind::Var,
+        };
+        self.bump_any();
+        // A lone name is always a binding, `_` is only a wildcard inside a
+        // list or map pattern
+        let target = if self.curr_token_kind() == TokenKind::Identifier
+            && self.next_token_kind() != TokenKind::Dot
+        {
+            let name = self.eat(TokenKind::Identifier)?;
+            Pattern::Binding(self.alloc(BindingPattern {
+                span: Span::new(name.from, name.to),
+                name: &self.source[name.from..name.to],
+            }))
+        } else {
+            self.parse_pattern()?
+        };
+        self.check_duplicate_bindings(&target, var_keyword.line);
+        if let Some(span) = target.first_value_pattern() {
+            return Err(LoxError::new(
+                var_keyword.line,
+                "Syntax Error: Literal and enum variant patterns can't be used in a declaration"
+                    .to_string(),
+            )
+            .with_span(span));
+        }
+
         let value = if self.curr_token_kind() == TokenKind::Equal {
             self.bump_any();
             Some(self.parse_expression()?)
         } else {
+            let required_by = match (kind, &target) {
+                (DeclarationKind::Const, _) => Some("const"),
+                (_, Pattern::Binding(_)) => None,
+                _ => Some("destructuring"),
+            };
+            if let Some(required_by) = required_by {
+                return Err(LoxError::new(
+                    var_keyword.line,
+                    format!(
+                        "Syntax Error: Missing initializer in {} declaration of {}",
+                        required_by,
+                        &self.source[target.span().from..target.span().to]
+                    ),
+                )
+                .with_span(Span::new(var_keyword.from, target.span().to)));
+            }
             None
         };
         let semi = self.eat(TokenKind::Semicolon)?;
         Ok(Statement::Declaration(Box::new_in(
             Declaration {
                 span: Span::new(var_keyword.from, semi.to),
-                name,
+                doc,
+                kind,
+                target,
                 value,
             },
             &self.allocator,
         )))
     }
 
+    /// The body of an `if`, `else`, `while` or `for`. A lone `let` or
+    /// `const` there would have no block to be scoped to.
+    fn parse_body_statement(&mut self) -> Result<Statement<'alloc>, LoxError> {
+        let body = self.parse_statement()?;
+        if let Statement::Declaration(declaration) = &body {
+            if let DeclarationKind::Let | DeclarationKind::Const = declaration.kind {
+                return Err(LoxError::new(
+                    declaration.span.line(self.source),
+                    "Syntax Error: A let or const declaration must be inside a block here"
+                        .to_string(),
+                )
+                .with_span(declaration.span));
+            }
+        }
+        Ok(body)
+    }
+
     fn parse_while_statement(&mut self) -> Result<Statement<'alloc>, LoxError> {
         let while_keyword = self.eat(TokenKind::While)?;
         self.eat(TokenKind::LeftParen)?;
         let condition = self.parse_expression()?;
         self.eat(TokenKind::RightParen)?;
-        let body = self.parse_block_statement()?;
+        let body = self.parse_body_statement()?;
         Ok(Statement::While(Box::new_in(
             While {
                 span: Span::new(while_keyword.from, body.span().to),
@@ -295,19 +739,28 @@ impl<'alloc> Parser<'alloc> {
     fn parse_for_statement(&mut self) -> Result<Statement<'alloc>, LoxError> {
         let for_keyword = self.eat(TokenKind::For)?;
         self.eat(TokenKind::LeftParen)?;
+        if self.curr_token_kind() == TokenKind::Identifier
+            && self.next_token_kind() == TokenKind::In
+        {
+            return self.parse_for_in_statement(for_keyword);
+        }
         let initializer = match self.curr_token_kind() {
-            TokenKind::Semicolon => None,
-            TokenKind::Var => Some(self.parse_variable_declaration()?),
+            TokenKind::Semicolon => {
+                self.bump_any();
+                None
+            }
+            TokenKind::Var | TokenKind::Let | TokenKind::Const => {
+                Some(self.parse_variable_declaration()?)
+            }
             _ => Some(self.parse_expression_statement()?),
         };
 
         let condiion = if self.curr_token_kind() != TokenKind::Semicolon {
-            let expr = self.parse_expression()?;
-            self.eat(TokenKind::Semicolon);
-            Some(expr)
+            Some(self.parse_expression()?)
         } else {
             None
         };
+        self.eat(TokenKind::Semicolon)?;
 
         let incrementor = if self.curr_token_kind() != TokenKind::RightParen {
             Some(self.parse_expression()?)
@@ -316,7 +769,7 @@ impl<'alloc> Parser<'alloc> {
         };
 
         self.eat(TokenKind::RightParen)?;
-        let body = self.parse_block_statement()?;
+        let body = self.parse_body_statement()?;
         Ok(Statement::For(Box::new_in(
             For {
                 span: Span::new(for_keyword.from, body.span().to),
@@ -329,6 +782,24 @@ impl<'alloc> Parser<'alloc> {
         )))
     }
 
+    /// The rest of `for (name in iterable) body` after the opening paren
+    fn parse_for_in_statement(
+        &mut self,
+        for_keyword: Token,
+    ) -> Result<Statement<'alloc>, LoxError> {
+        let variable = self.eat(TokenKind::Identifier)?;
+        self.eat(TokenKind::In)?;
+        let iterable = self.parse_expression()?;
+        self.eat(TokenKind::RightParen)?;
+        let body = self.parse_body_statement()?;
+        Ok(Statement::ForIn(self.alloc(ForIn {
+            span: Span::new(for_keyword.from, body.span().to),
+            variable,
+            iterable,
+            body,
+        })))
+    }
+
     fn parse_block_statement(&mut self) -> Result<Statement<'alloc>, LoxError> {
         let start_brace = self.eat(TokenKind::LeftBrace)?;
         let mut body = BumpVec::new_in(&self.allocator);
@@ -355,25 +826,212 @@ impl<'alloc> Parser<'alloc> {
         self.eat(TokenKind::LeftParen)?;
         let condition = self.parse_expression()?;
         self.eat(TokenKind::RightParen)?;
-        let body = self.parse_block_statement()?;
+        let body = self.parse_body_statement()?;
+        // An `else` always binds to the nearest `if`, which falls out of
+        // parsing the body before looking for the `else` keyword.
         let else_branch = if self.curr_token_kind() == TokenKind::Else {
             self.bump_any();
-            if self.curr_token_kind() == TokenKind::If {
-                Some(self.parse_if_statement()?)
-            } else {
-                Some(self.parse_block_statement()?)
-            }
+            Some(self.parse_body_statement()?)
         } else {
             None
         };
+        let end = else_branch.as_ref().unwrap_or(&body).span().to;
         Ok(Statement::If(self.alloc(If {
-            span: Span::new(if_keyword.from, body.span().to),
+            span: Span::new(if_keyword.from, end),
             condition,
             body,
             else_branch,
         })))
     }
 
+    fn parse_match_statement(&mut self) -> Result<Statement<'alloc>, LoxError> {
+        let match_keyword = self.eat(TokenKind::Match)?;
+        self.eat(TokenKind::LeftParen)?;
+        let subject = self.parse_expression()?;
+        self.eat(TokenKind::RightParen)?;
+        self.eat(TokenKind::LeftBrace)?;
+
+        let mut arms: Vec<MatchArm<'alloc>> = Vec::new();
+        while self.curr_token_kind() != TokenKind::RightBrace {
+            let pattern_token = self.curr_token();
+            let pattern = self.parse_pattern()?;
+            self.check_duplicate_bindings(&pattern, pattern_token.line);
+            self.eat(TokenKind::FatArrow)?;
+            let body = self.parse_statement()?;
+            if self.curr_token_kind() == TokenKind::Comma {
+                self.bump_any();
+            }
+
+            // Reported without bailing out so the rest of the match is still checked
+            if arms.iter().any(|arm| arm.pattern.is_irrefutable()) {
+                self.add_error(
+                    LoxError::new(
+                        pattern_token.line,
+                        "Unreachable match arm, an earlier arm matches every value".to_string(),
+                    )
+                    .with_span(pattern.span()),
+                );
+            }
+
+            arms.push(MatchArm {
+                span: Span::new(pattern.span().from, body.span().to),
+                pattern,
+                body,
+            });
+        }
+
+        let end_brace = self.eat(TokenKind::RightBrace)?;
+        Ok(Statement::Match(self.alloc(Match {
+            span: Span::new(match_keyword.from, end_brace.to),
+            subject,
+            arms,
+        })))
+    }
+
+    fn parse_pattern(&mut self) -> Result<Pattern<'alloc>, LoxError> {
+        let curr_token = self.curr_token();
+        let span = Span::new(curr_token.from, curr_token.to);
+
+        let pattern = match curr_token.kind {
+            TokenKind::LeftBracket => return self.parse_list_pattern(),
+            TokenKind::LeftBrace => return self.parse_map_pattern(),
+            TokenKind::Identifier if self.next_token_kind() == TokenKind::Dot => {
+                let enum_name = self.curr_token_lexeme();
+                self.bump_any();
+                self.bump_any();
+                let variant = self.eat(TokenKind::Identifier)?;
+                return Ok(Pattern::Variant(self.alloc(VariantPattern {
+                    span: Span::new(curr_token.from, variant.to),
+                    enum_name,
+                    variant: &self.source[variant.from..variant.to],
+                })));
+            }
+            TokenKind::Identifier if self.curr_token_lexeme() == "_" => {
+                Pattern::Wildcard(self.alloc(WildcardPattern { span }))
+            }
+            TokenKind::Identifier => Pattern::Binding(self.alloc(BindingPattern {
+                span,
+                name: self.curr_token_lexeme(),
+            })),
+            TokenKind::String
+            | TokenKind::True
+            | TokenKind::False
+            | TokenKind::Nil
+            | TokenKind::Number => Pattern::Literal(self.alloc(LiteralPattern {
+                span,
+                value: self.get_literal(curr_token)?,
+            })),
+            // Negative number literals, folded into a single literal
+            TokenKind::Minus if self.next_token_kind() == TokenKind::Number => {
+                self.bump_any();
+                let number = self.curr_token();
+                let span = Span::new(curr_token.from, number.to);
+                let LiteralValue::Number(literal) = self.get_literal(number)? else {
+                    unreachable!("number tokens produce number literals")
+                };
+                Pattern::Literal(self.alloc(LiteralPattern {
+                    span,
+                    value: LiteralValue::Number(NumberLiteral {
+                        span,
+                        raw: &self.source[span.from..span.to],
+                        value: -literal.value,
+                    }),
+                }))
+            }
+            _ => {
+                return Err(LoxError::new(
+                    curr_token.line,
+                    format!(
+                        "Syntax Error: Pattern expected but got {}",
+                        self.curr_token_lexeme(),
+                    ),
+                )
+                .with_span(span))
+            }
+        };
+        self.bump_any();
+        Ok(pattern)
+    }
+
+    /// Reported without bailing out, like duplicate enum variants
+    fn check_duplicate_bindings(&mut self, pattern: &Pattern<'alloc>, line: usize) {
+        let bindings = pattern.bindings();
+        for (index, binding) in bindings.iter().enumerate() {
+            if let Some(first) = bindings[..index]
+                .iter()
+                .find(|first| first.name == binding.name)
+            {
+                self.add_error(
+                    LoxError::new(
+                        line,
+                        format!(
+                            "Syntax Error: Duplicate binding {} in pattern",
+                            binding.name
+                        ),
+                    )
+                    .with_span(binding.span)
+                    .with_label(first.span, format!("{} is first bound here", first.name)),
+                );
+            }
+        }
+    }
+
+    fn parse_list_pattern(&mut self) -> Result<Pattern<'alloc>, LoxError> {
+        let start_bracket = self.eat(TokenKind::LeftBracket)?;
+        let mut elements = Vec::new();
+
+        while self.curr_token_kind() != TokenKind::RightBracket {
+            elements.push(self.parse_pattern()?);
+            if self.curr_token_kind() == TokenKind::Comma {
+                self.bump_any();
+            } else {
+                break;
+            }
+        }
+
+        let end_bracket = self.eat(TokenKind::RightBracket)?;
+        Ok(Pattern::List(self.alloc(ListPattern {
+            span: Span::new(start_bracket.from, end_bracket.to),
+            elements,
+        })))
+    }
+
+    fn parse_map_pattern(&mut self) -> Result<Pattern<'alloc>, LoxError> {
+        let start_brace = self.eat(TokenKind::LeftBrace)?;
+        let mut entries = Vec::new();
+
+        while self.curr_token_kind() != TokenKind::RightBrace {
+            let key = self.eat(TokenKind::Identifier)?;
+            let key_span = Span::new(key.from, key.to);
+            let key = &self.source[key.from..key.to];
+            let pattern = if self.curr_token_kind() == TokenKind::Colon {
+                self.bump_any();
+                self.parse_pattern()?
+            } else {
+                Pattern::Binding(self.alloc(BindingPattern {
+                    span: key_span,
+                    name: key,
+                }))
+            };
+            entries.push(MapPatternEntry {
+                span: Span::new(key_span.from, pattern.span().to),
+                key,
+                pattern,
+            });
+            if self.curr_token_kind() == TokenKind::Comma {
+                self.bump_any();
+            } else {
+                break;
+            }
+        }
+
+        let end_brace = self.eat(TokenKind::RightBrace)?;
+        Ok(Pattern::Map(self.alloc(MapPattern {
+            span: Span::new(start_brace.from, end_brace.to),
+            entries,
+        })))
+    }
+
     fn parse_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
         self.parse_assignment_expression()
     }
@@ -390,43 +1048,190 @@ impl<'alloc> Parser<'alloc> {
 
     fn parse_return_statement(&mut self) -> Result<Statement<'alloc>, LoxError> {
         let return_keyword = self.eat(TokenKind::Return)?;
-        let expr = self.parse_expression()?;
-        self.eat(TokenKind::Semicolon)?;
+        let value = if self.curr_token_kind() != TokenKind::Semicolon {
+            Some(self.parse_expression()?)
+        } else {
+            None
+        };
+        let semi = self.eat(TokenKind::Semicolon)?;
         Ok(Statement::Return(self.alloc(Return {
-            span: Span::new(return_keyword.from, expr.span().to),
-            value: expr,
+            span: Span::new(return_keyword.from, semi.to),
+            value,
         })))
     }
 
-    fn parse_assignment_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
-        let expr = self.parse_or_expression()?;
-        if self.curr_token_kind() == TokenKind::Equal {
-            self.bump_any();
-            let right = self.parse_assignment_expression()?;
-            if let Expression::Variable(_) = expr {
-                let span = Span::new(expr.span().from, right.span().to);
-                return Ok(Expression::Assignment(self.alloc(Assignment {
-                    span,
-                    target: expr,
-                    value: right,
-                })));
-            } else {
-                todo!("Expected variable but got {:?}", 1)
-            }
-        }
-        Ok(expr)
+    fn invalid_assignment_target(&self, target: &Expression<'alloc>) -> LoxError {
+        LoxError::new(
+            target.span().line(self.source),
+            "Syntax Error: Invalid assignment target".to_string(),
+        )
+        .with_span(target.span())
     }
 
-    fn parse_or_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
-        let expr = self.parse_and_expression()?;
+    fn parse_throw_statement(&mut self) -> Result<Statement<'alloc>, LoxError> {
+        let throw_keyword = self.eat(TokenKind::Throw)?;
+        let value = self.parse_expression()?;
+        let semi = self.eat(TokenKind::Semicolon)?;
+        Ok(Statement::Throw(self.alloc(Throw {
+            span: Span::new(throw_keyword.from, semi.to),
+            value,
+        })))
+    }
 
-        if self.curr_token_kind() == TokenKind::Or {
-            let operator = self.operator()?;
-            self.bump_any();
-            let right = self.parse_or_expression()?;
-            return Ok(Expression::Logical(self.alloc(Logical {
-                span: Span::new(expr.span().from, right.span().to),
-                left: expr,
+    fn parse_try_statement(&mut self) -> Result<Statement<'alloc>, LoxError> {
+        let try_keyword = self.eat(TokenKind::Try)?;
+        let body = self.parse_block_statement()?;
+        let mut end = body.span().to;
+
+        let catch = if self.curr_token_kind() == TokenKind::Catch {
+            let catch_keyword = self.eat(TokenKind::Catch)?;
+            self.eat(TokenKind::LeftParen)?;
+            let param = self.eat(TokenKind::Identifier)?;
+            self.eat(TokenKind::RightParen)?;
+            let body = self.parse_block_statement()?;
+            end = body.span().to;
+            Some(Catch {
+                span: Span::new(catch_keyword.from, end),
+                param,
+                body,
+            })
+        } else {
+            None
+        };
+
+        let finally = if self.curr_token_kind() == TokenKind::Finally {
+            self.bump_any();
+            let body = self.parse_block_statement()?;
+            end = body.span().to;
+            Some(body)
+        } else {
+            None
+        };
+
+        let span = Span::new(try_keyword.from, end);
+        if catch.is_none() && finally.is_none() {
+            return Err(LoxError::new(
+                try_keyword.line,
+                "Syntax Error: Expected 'catch' or 'finally' after try block".to_string(),
+            )
+            .with_span(span));
+        }
+
+        Ok(Statement::Try(self.alloc(Try {
+            span,
+            body,
+            catch,
+            finally,
+        })))
+    }
+
+    fn parse_assignment_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
+        if self.curr_token_kind() == TokenKind::Yield {
+            return self.parse_yield_expression();
+        }
+        let expr = self.parse_nil_coalescing_expression()?;
+        match self.curr_token_kind() {
+            TokenKind::Equal => {
+                self.bump_any();
+                let right = self.parse_assignment_expression()?;
+                if !expr.is_assignment_target() {
+                    return Err(self.invalid_assignment_target(&expr));
+                }
+                let span = Span::new(expr.span().from, right.span().to);
+                Ok(Expression::Assignment(self.alloc(Assignment {
+                    span,
+                    target: expr,
+                    value: right,
+                })))
+            }
+            TokenKind::PlusEqual
+            | TokenKind::MinusEqual
+            | TokenKind::StarEqual
+            | TokenKind::SlashEqual => {
+                let operator = self.operator()?;
+                self.bump_any();
+                let right = self.parse_assignment_expression()?;
+                if !expr.is_assignment_target() {
+                    return Err(self.invalid_assignment_target(&expr));
+                }
+                let span = Span::new(expr.span().from, right.span().to);
+                Ok(Expression::CompoundAssignment(self.alloc(
+                    CompoundAssignment {
+                        span,
+                        target: expr,
+                        value: right,
+                        operator,
+                    },
+                )))
+            }
+            _ => Ok(expr),
+        }
+    }
+
+    fn parse_yield_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
+        let yield_keyword = self.eat(TokenKind::Yield)?;
+        let message = match self.yields.last_mut() {
+            Some(YieldContext::Body(is_generator)) => {
+                *is_generator = true;
+                None
+            }
+            Some(YieldContext::Parameters) => Some("'yield' can't be used in a parameter default"),
+            None => Some("'yield' outside of a function"),
+        };
+        if let Some(message) = message {
+            return Err(
+                LoxError::new(yield_keyword.line, format!("Syntax Error: {}", message))
+                    .with_span(Span::new(yield_keyword.from, yield_keyword.to)),
+            );
+        }
+
+        // A bare `yield` ends where the enclosing construct does
+        let value = match self.curr_token_kind() {
+            TokenKind::Semicolon
+            | TokenKind::RightParen
+            | TokenKind::RightBracket
+            | TokenKind::RightBrace
+            | TokenKind::Comma
+            | TokenKind::Colon => None,
+            _ => Some(self.parse_assignment_expression()?),
+        };
+        let end = value
+            .as_ref()
+            .map_or(yield_keyword.to, |value| value.span().to);
+        Ok(Expression::Yield(self.alloc(Yield {
+            span: Span::new(yield_keyword.from, end),
+            value,
+        })))
+    }
+
+    // `??` binds looser than `or`: `a ?? b or c` is `a ?? (b or c)`
+    fn parse_nil_coalescing_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
+        let expr = self.parse_or_expression()?;
+
+        if self.curr_token_kind() == TokenKind::QuestionQuestion {
+            let operator = self.operator()?;
+            self.bump_any();
+            let right = self.parse_nil_coalescing_expression()?;
+            return Ok(Expression::Logical(self.alloc(Logical {
+                span: Span::new(expr.span().from, right.span().to),
+                left: expr,
+                right,
+                operator,
+            })));
+        }
+        Ok(expr)
+    }
+
+    fn parse_or_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
+        let expr = self.parse_and_expression()?;
+
+        if self.curr_token_kind() == TokenKind::Or {
+            let operator = self.operator()?;
+            self.bump_any();
+            let right = self.parse_or_expression()?;
+            return Ok(Expression::Logical(self.alloc(Logical {
+                span: Span::new(expr.span().from, right.span().to),
+                left: expr,
                 right,
                 operator,
             })));
@@ -435,12 +1240,12 @@ impl<'alloc> Parser<'alloc> {
     }
 
     fn parse_and_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
-        let expr = self.parse_equality_expression()?;
+        let expr = self.parse_bitwise_or_expression()?;
 
         if self.curr_token_kind() == TokenKind::And {
             let operator = self.operator()?;
             self.bump_any();
-            let right = self.parse_or_expression()?;
+            let right = self.parse_and_expression()?;
             return Ok(Expression::Logical(self.alloc(Logical {
                 span: Span::new(expr.span().from, right.span().to),
                 left: expr,
@@ -451,74 +1256,161 @@ impl<'alloc> Parser<'alloc> {
         Ok(expr)
     }
 
+    // The bitwise operators follow C, so they bind looser than equality:
+    // `a & b == c` is `a & (b == c)`.
+    fn parse_bitwise_or_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
+        let mut expr = self.parse_bitwise_xor_expression()?;
+        while self.curr_token_kind() == TokenKind::Pipe {
+            let operator = self.operator()?;
+            self.bump_any();
+            let right = self.parse_bitwise_xor_expression()?;
+            expr = Expression::Binary(self.alloc(Binary {
+                span: Span::new(expr.span().from, right.span().to),
+                left: expr,
+                right,
+                operator,
+            }));
+        }
+        Ok(expr)
+    }
+
+    fn parse_bitwise_xor_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
+        let mut expr = self.parse_bitwise_and_expression()?;
+        while self.curr_token_kind() == TokenKind::Caret {
+            let operator = self.operator()?;
+            self.bump_any();
+            let right = self.parse_bitwise_and_expression()?;
+            expr = Expression::Binary(self.alloc(Binary {
+                span: Span::new(expr.span().from, right.span().to),
+                left: expr,
+                right,
+                operator,
+            }));
+        }
+        Ok(expr)
+    }
+
+    fn parse_bitwise_and_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
+        let mut expr = self.parse_equality_expression()?;
+        while self.curr_token_kind() == TokenKind::Ampersand {
+            let operator = self.operator()?;
+            self.bump_any();
+            let right = self.parse_equality_expression()?;
+            expr = Expression::Binary(self.alloc(Binary {
+                span: Span::new(expr.span().from, right.span().to),
+                left: expr,
+                right,
+                operator,
+            }));
+        }
+        Ok(expr)
+    }
+
     fn parse_equality_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
-        let expr = self.parse_comparison_expression()?;
+        let mut expr = self.parse_comparison_expression()?;
 
-        if matches!(
+        while matches!(
             self.curr_token_kind(),
             TokenKind::EqualEqual | TokenKind::BangEqual
         ) {
             let operator = self.operator()?;
             self.bump_any();
             let right = self.parse_comparison_expression()?;
-            return Ok(Expression::Binary(self.alloc(Binary {
+            expr = Expression::Binary(self.alloc(Binary {
                 span: Span::new(expr.span().from, right.span().to),
                 left: expr,
                 right,
                 operator,
-            })));
+            }));
         }
         Ok(expr)
     }
 
     fn parse_comparison_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
-        let expr = self.parse_term_expression()?;
+        let mut expr = self.parse_range_expression()?;
 
-        if matches!(
+        while matches!(
             self.curr_token_kind(),
             TokenKind::Greater | TokenKind::GreaterEqual | TokenKind::Less | TokenKind::LessEqual
         ) {
             let operator = self.operator()?;
             self.bump_any();
-            let right = self.parse_term_expression()?;
-            return Ok(Expression::Binary(self.alloc(Binary {
+            let right = self.parse_range_expression()?;
+            expr = Expression::Binary(self.alloc(Binary {
                 span: Span::new(expr.span().from, right.span().to),
                 left: expr,
                 right,
                 operator,
+            }));
+        }
+        Ok(expr)
+    }
+
+    /// Ranges don't chain, `a..b..c` is a syntax error
+    fn parse_range_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
+        let start = self.parse_shift_expression()?;
+        if self.curr_token_kind() == TokenKind::DotDot {
+            self.bump_any();
+            let end = self.parse_shift_expression()?;
+            return Ok(Expression::Range(self.alloc(Range {
+                span: Span::new(start.span().from, end.span().to),
+                start,
+                end,
             })));
         }
+        Ok(start)
+    }
+
+    fn parse_shift_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
+        let mut expr = self.parse_term_expression()?;
+        while matches!(
+            self.curr_token_kind(),
+            TokenKind::LessLess | TokenKind::GreaterGreater
+        ) {
+            let operator = self.operator()?;
+            self.bump_any();
+            let right = self.parse_term_expression()?;
+            expr = Expression::Binary(self.alloc(Binary {
+                span: Span::new(expr.span().from, right.span().to),
+                left: expr,
+                right,
+                operator,
+            }));
+        }
         Ok(expr)
     }
 
     fn parse_term_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
-        let expr = self.parse_factor_expression()?;
-        if matches!(self.curr_token_kind(), TokenKind::Plus | TokenKind::Minus) {
+        let mut expr = self.parse_factor_expression()?;
+        while matches!(self.curr_token_kind(), TokenKind::Plus | TokenKind::Minus) {
             let operator = self.operator()?;
             self.bump_any();
             let right = self.parse_factor_expression()?;
-            return Ok(Expression::Binary(self.alloc(Binary {
+            expr = Expression::Binary(self.alloc(Binary {
                 span: Span::new(expr.span().from, right.span().to),
                 left: expr,
                 right,
                 operator,
-            })));
+            }));
         }
         Ok(expr)
     }
 
     fn parse_factor_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
-        let expr = self.parse_unary_expression()?;
-        if matches!(self.curr_token_kind(), TokenKind::Star | TokenKind::Slash) {
+        let mut expr = self.parse_unary_expression()?;
+        while matches!(
+            self.curr_token_kind(),
+            TokenKind::Star | TokenKind::Slash | TokenKind::Percent | TokenKind::TildeSlash
+        ) {
             let operator = self.operator()?;
             self.bump_any();
             let right = self.parse_unary_expression()?;
-            return Ok(Expression::Binary(self.alloc(Binary {
+            expr = Expression::Binary(self.alloc(Binary {
                 span: Span::new(expr.span().from, right.span().to),
                 left: expr,
                 right,
                 operator,
-            })));
+            }));
         }
         Ok(expr)
     }
@@ -527,7 +1419,10 @@ impl<'alloc> Parser<'alloc> {
         let curr_token = self.curr_token();
         let span = Span::default().start(curr_token.from);
 
-        if matches!(curr_token.kind, TokenKind::Bang | TokenKind::Minus) {
+        if matches!(
+            curr_token.kind,
+            TokenKind::Bang | TokenKind::Minus | TokenKind::Tilde
+        ) {
             let operator = self.operator()?;
             self.bump_any();
             let right = self.parse_unary_expression()?;
@@ -537,42 +1432,270 @@ impl<'alloc> Parser<'alloc> {
                 right,
             })));
         }
-        self.parse_primary_expression()
+        if matches!(curr_token.kind, TokenKind::PlusPlus | TokenKind::MinusMinus) {
+            let operator = self.operator()?;
+            self.bump_any();
+            let target = self.parse_unary_expression()?;
+            if !target.is_assignment_target() {
+                return Err(self.invalid_assignment_target(&target));
+            }
+            return Ok(Expression::Update(self.alloc(Update {
+                span: span.end(target.span().to),
+                target,
+                operator,
+                prefix: true,
+            })));
+        }
+        self.parse_exponent_expression()
     }
 
-    fn parse_call_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
-        let expr = self.parse_primary_expression()?;
-        if self.curr_token_kind() == TokenKind::LeftParen {
+    /// `**` is right associative and its right operand may itself be a unary
+    /// expression, so `-2 ** 2` is `-(2 ** 2)` while `2 ** -1` still parses.
+    fn parse_exponent_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
+        let expr = self.parse_postfix_expression()?;
+        if self.curr_token_kind() == TokenKind::StarStar {
+            let operator = self.operator()?;
             self.bump_any();
-            let span = Span::default().start(expr.span().from);
-            let mut arguments = Vec::new();
+            let right = self.parse_unary_expression()?;
+            return Ok(Expression::Binary(self.alloc(Binary {
+                span: Span::new(expr.span().from, right.span().to),
+                left: expr,
+                right,
+                operator,
+            })));
+        }
+        Ok(expr)
+    }
 
-            while self.curr_token_kind() == TokenKind::Comma {
-                self.bump_any();
+    fn parse_postfix_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
+        let expr = self.parse_call_expression()?;
+        if matches!(
+            self.curr_token_kind(),
+            TokenKind::PlusPlus | TokenKind::MinusMinus
+        ) {
+            if !expr.is_assignment_target() {
+                return Err(self.invalid_assignment_target(&expr));
+            }
+            let operator = self.operator()?;
+            let operator_token = self.curr_token();
+            self.bump_any();
+            return Ok(Expression::Update(self.alloc(Update {
+                span: Span::new(expr.span().from, operator_token.to),
+                target: expr,
+                operator,
+                prefix: false,
+            })));
+        }
+        Ok(expr)
+    }
+
+    fn parse_call_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
+        let mut expr = self.parse_primary_expression()?;
+        loop {
+            expr = match self.curr_token_kind() {
+                TokenKind::LeftParen => self.finish_call_expression(expr, false)?,
+                TokenKind::LeftBracket => self.finish_index_expression(expr)?,
+                TokenKind::Dot => self.finish_get_expression(expr, false)?,
+                TokenKind::QuestionDot if self.next_token_kind() == TokenKind::LeftParen => {
+                    self.bump_any();
+                    self.finish_call_expression(expr, true)?
+                }
+                TokenKind::QuestionDot => self.finish_get_expression(expr, true)?,
+                _ => return Ok(expr),
+            };
+        }
+    }
+
+    fn finish_call_expression(
+        &mut self,
+        callee: Expression<'alloc>,
+        optional: bool,
+    ) -> Result<Expression<'alloc>, LoxError> {
+        self.eat(TokenKind::LeftParen)?;
+        let span = Span::default().start(callee.span().from);
+        let mut arguments = Vec::new();
+
+        if self.curr_token_kind() != TokenKind::RightParen {
+            loop {
                 arguments.push(self.parse_expression()?);
+                if self.curr_token_kind() == TokenKind::Comma {
+                    self.bump_any();
+                } else {
+                    break;
+                }
             }
+        }
 
-            // if the current token is a end paren
-            // return the call expression
-            // else throw parse error
-            if self.curr_token_kind() == TokenKind::RightParen {
-                let end_paren = self.curr_token();
-                self.bump_any();
+        // if the current token is a end paren
+        // return the call expression
+        // else throw parse error
+        if self.curr_token_kind() == TokenKind::RightParen {
+            let end_paren = self.curr_token();
+            self.bump_any();
 
-                return Ok(Expression::Call(self.alloc(Call {
-                    span: span.end(end_paren.to),
-                    callee: expr,
-                    arguments,
-                    end_paren,
-                })));
-            } else {
+            Ok(Expression::Call(self.alloc(Call {
+                span: span.end(end_paren.to),
+                callee,
+                arguments,
+                end_paren,
+                optional,
+            })))
+        } else {
+            Err(LoxError::new(
+                self.curr_token().line,
+                "Expected ')' after arguments".to_string(),
+            ))
+        }
+    }
+
+    /// Called on the `.` or `?.` before the field name
+    fn finish_get_expression(
+        &mut self,
+        object: Expression<'alloc>,
+        optional: bool,
+    ) -> Result<Expression<'alloc>, LoxError> {
+        self.bump_any();
+        let name = self.eat(TokenKind::Identifier)?;
+        Ok(Expression::Get(self.alloc(Get {
+            span: Span::new(object.span().from, name.to),
+            object,
+            name: &self.source[name.from..name.to],
+            optional,
+        })))
+    }
+
+    fn finish_index_expression(
+        &mut self,
+        object: Expression<'alloc>,
+    ) -> Result<Expression<'alloc>, LoxError> {
+        self.eat(TokenKind::LeftBracket)?;
+        let index = self.parse_expression()?;
+        let end_bracket = self.eat(TokenKind::RightBracket)?;
+        Ok(Expression::Index(self.alloc(Index {
+            span: Span::new(object.span().from, end_bracket.to),
+            object,
+            index,
+        })))
+    }
+
+    /// A statement starting with `{` is a block unless `is_map_statement`
+    /// finds a key or an operator after it
+    fn parse_map_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
+        let start_brace = self.eat(TokenKind::LeftBrace)?;
+        let mut entries = Vec::new();
+
+        // Entries are separated by commas, a trailing comma is allowed
+        while self.curr_token_kind() != TokenKind::RightBrace {
+            let key = self.parse_expression()?;
+            if let Some(kind) = Self::invalid_map_key_kind(&key) {
                 return Err(LoxError::new(
                     self.curr_token().line,
-                    "Expected ')' after arguments".to_string(),
-                ));
+                    format!(
+                        "Syntax Error: Map keys must be strings, numbers or booleans, got {}",
+                        kind
+                    ),
+                )
+                .with_span(key.span()));
+            }
+            self.eat(TokenKind::Colon)?;
+            let value = self.parse_expression()?;
+            entries.push(MapEntry {
+                span: Span::new(key.span().from, value.span().to),
+                key,
+                value,
+            });
+            if self.curr_token_kind() == TokenKind::Comma {
+                self.bump_any();
+            } else {
+                break;
+            }
+        }
+
+        let end_brace = self.eat(TokenKind::RightBrace)?;
+        Ok(Expression::Map(self.alloc(Map {
+            span: Span::new(start_brace.from, end_brace.to),
+            entries,
+        })))
+    }
+
+    /// Keys that can be rejected without running the program
+    fn invalid_map_key_kind(key: &Expression<'alloc>) -> Option<&'static str> {
+        match key {
+            Expression::Lambda(_) => Some("a function"),
+            Expression::List(_) => Some("a list"),
+            Expression::Map(_) => Some("a map"),
+            Expression::Literal(literal) if matches!(literal.value, LiteralValue::Nil(_)) => {
+                Some("nil")
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_list_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
+        let start_bracket = self.eat(TokenKind::LeftBracket)?;
+        let mut elements = Vec::new();
+
+        // Elements are separated by commas, a trailing comma is allowed
+        while self.curr_token_kind() != TokenKind::RightBracket {
+            elements.push(self.parse_expression()?);
+            if self.curr_token_kind() == TokenKind::Comma {
+                self.bump_any();
+            } else {
+                break;
+            }
+        }
+
+        let end_bracket = self.eat(TokenKind::RightBracket)?;
+        Ok(Expression::List(self.alloc(List {
+            span: Span::new(start_bracket.from, end_bracket.to),
+            elements,
+        })))
+    }
+
+    fn parse_lambda_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
+        let fun_keyword = self.eat(TokenKind::Fun)?;
+        let (params, body, is_generator) = self.parse_function_params_and_body()?;
+        Ok(Expression::Lambda(self.alloc(Lambda {
+            span: Span::new(fun_keyword.from, body.span().to),
+            params,
+            body,
+            is_generator,
+        })))
+    }
+
+    fn parse_interpolation_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
+        let start = self.eat(TokenKind::InterpolationStart)?;
+        let mut parts = Vec::new();
+        parts.extend(self.interpolation_part(start, 2));
+
+        loop {
+            parts.push(InterpolationPart::Expression(self.parse_expression()?));
+            let curr_token = self.curr_token();
+            match curr_token.kind {
+                TokenKind::InterpolationMiddle => {
+                    self.bump_any();
+                    parts.extend(self.interpolation_part(curr_token, 2));
+                }
+                TokenKind::InterpolationEnd => {
+                    self.bump_any();
+                    parts.extend(self.interpolation_part(curr_token, 1));
+                    return Ok(Expression::Interpolation(self.alloc(Interpolation {
+                        span: Span::new(start.from, curr_token.to),
+                        parts,
+                    })));
+                }
+                _ => {
+                    return Err(LoxError::new(
+                        curr_token.line,
+                        format!(
+                            "Syntax Error: Expected '}}' after interpolated expression, got {}",
+                            self.curr_token_lexeme(),
+                        ),
+                    )
+                    .with_span(Span::new(curr_token.from, curr_token.to)))
+                }
             }
         }
-        Ok(expr)
     }
 
     fn parse_primary_expression(&mut self) -> Result<Expression<'alloc>, LoxError> {
@@ -592,23 +1715,28 @@ impl<'alloc> Parser<'alloc> {
                 span,
                 name: self.curr_token_lexeme(),
             })),
-            TokenKind::LeftBrace => {
-                let expr = self.parse_expression()?;
-                let curr_token = self.curr_token();
-                if curr_token.kind == TokenKind::RightBrace {
-                    self.bump_any();
-                    Expression::Grouping(self.alloc(Grouping {
-                        span: Span::new(curr_token.from, curr_token.to),
-                        expression: expr,
-                    }));
-                }
-                return Err(LoxError::new(
-                    curr_token.line,
-                    format!(
-                        "Syntax Error: Expected '}}', got {}",
-                        self.curr_token_lexeme(),
-                    ),
-                ));
+            TokenKind::This => Expression::This(self.alloc(This { span })),
+            TokenKind::Super => {
+                self.bump_any();
+                self.eat(TokenKind::Dot)?;
+                let method = self.eat(TokenKind::Identifier)?;
+                return Ok(Expression::Super(self.alloc(Super {
+                    span: Span::new(curr_token.from, method.to),
+                    method: &self.source[method.from..method.to],
+                })));
+            }
+            TokenKind::InterpolationStart => return self.parse_interpolation_expression(),
+            TokenKind::Fun => return self.parse_lambda_expression(),
+            TokenKind::LeftBracket => return self.parse_list_expression(),
+            TokenKind::LeftBrace => return self.parse_map_expression(),
+            TokenKind::LeftParen => {
+                self.bump_any();
+                let expression = self.parse_expression()?;
+                let end_paren = self.eat(TokenKind::RightParen)?;
+                return Ok(Expression::Grouping(self.alloc(Grouping {
+                    span: Span::new(curr_token.from, end_paren.to),
+                    expression,
+                })));
             }
             _ => Err(LoxError::new(
                 curr_token.line,
@@ -737,3 +1865,1411 @@ pub fn test_parse_while_loop() {
         }
     }
 }
+
+#[test]
+pub fn test_parse_non_block_bodies() {
+    let source = "
+		if (x) print x;
+		while (c) i = i + 1;
+		for (;;) print i;
+	";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("non-block bodies should parse");
+    assert_eq!(ast.body.len(), 3);
+    match &ast.body[0] {
+        Statement::If(if_) => {
+            assert!(matches!(if_.body, Statement::Print(_)));
+            assert!(if_.else_branch.is_none());
+        }
+        stmt => panic!("Expected if statement but got {:?}", stmt),
+    }
+    match &ast.body[1] {
+        Statement::While(while_) => assert!(matches!(while_.body, Statement::Expression(_))),
+        stmt => panic!("Expected while loop but got {:?}", stmt),
+    }
+    match &ast.body[2] {
+        Statement::For(for_) => {
+            assert!(for_.initializer.is_none());
+            assert!(for_.condition.is_none());
+            assert!(for_.increment.is_none());
+            assert!(matches!(for_.body, Statement::Print(_)));
+        }
+        stmt => panic!("Expected for loop but got {:?}", stmt),
+    }
+}
+
+#[test]
+pub fn test_parse_dangling_else() {
+    let source = "if (a) if (b) print 1; else print 2;";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("dangling else should parse");
+    assert_eq!(ast.body.len(), 1);
+    let Statement::If(outer) = &ast.body[0] else {
+        panic!("Expected if statement but got {:?}", ast.body[0]);
+    };
+    assert!(outer.else_branch.is_none());
+    let Statement::If(inner) = &outer.body else {
+        panic!("Expected nested if statement but got {:?}", outer.body);
+    };
+    assert!(matches!(inner.else_branch, Some(Statement::Print(_))));
+    assert_eq!(outer.span, inner.span.start(outer.span.from));
+}
+
+#[test]
+pub fn test_parse_bare_return() {
+    let source = "
+		fun early(x) {
+			if (x) return;
+			return x;
+		}
+	";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("bare return should parse");
+    let Statement::Function(function) = &ast.body[0] else {
+        panic!("Expected function but got {:?}", ast.body[0]);
+    };
+    let Statement::Block(block) = &function.body else {
+        panic!("Expected function body block but got {:?}", function.body);
+    };
+    let Statement::If(if_) = &block.body[0] else {
+        panic!("Expected if statement but got {:?}", block.body[0]);
+    };
+    assert!(matches!(&if_.body, Statement::Return(ret) if ret.value.is_none()));
+    assert!(matches!(&block.body[1], Statement::Return(ret) if ret.value.is_some()));
+}
+
+#[test]
+pub fn test_parse_compound_assignment_and_update() {
+    let source = "
+		for (var i = 0; i < length; i++) {
+			total += i;
+			--i;
+		}
+	";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("compound assignment should parse");
+    let Statement::For(for_) = &ast.body[0] else {
+        panic!("Expected for loop but got {:?}", ast.body[0]);
+    };
+    assert!(matches!(
+        &for_.increment,
+        Some(Expression::Update(update))
+            if !update.prefix && matches!(update.operator, Operator::PlusPlus(_))
+    ));
+    let Statement::Block(block) = &for_.body else {
+        panic!("Expected block but got {:?}", for_.body);
+    };
+    let Statement::Expression(first) = &block.body[0] else {
+        panic!("Expected expression statement but got {:?}", block.body[0]);
+    };
+    assert!(matches!(
+        &first.expression,
+        Expression::CompoundAssignment(assignment)
+            if matches!(assignment.operator, Operator::PlusEqual(_))
+    ));
+    let Statement::Expression(second) = &block.body[1] else {
+        panic!("Expected expression statement but got {:?}", block.body[1]);
+    };
+    assert!(matches!(&second.expression, Expression::Update(update) if update.prefix));
+}
+
+#[test]
+pub fn test_parse_invalid_update_target() {
+    let source = "1++; 2 += 3;";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let errors = parser.parse().expect_err("literals are not assignable");
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].span, Some(Span::new(0, 1)));
+    assert_eq!(errors[1].span, Some(Span::new(5, 6)));
+}
+
+#[test]
+pub fn test_parse_arithmetic_precedence() {
+    let source = "-2 ** 3 ** 2 % 5 ~/ 2 - 1 - 1;";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("arithmetic should parse");
+    let Statement::Expression(stmt) = &ast.body[0] else {
+        panic!("Expected expression statement but got {:?}", ast.body[0]);
+    };
+    // ((((-(2 ** (3 ** 2))) % 5) ~/ 2) - 1) - 1
+    let Expression::Binary(outer) = &stmt.expression else {
+        panic!("Expected binary expression but got {:?}", stmt.expression);
+    };
+    assert!(matches!(outer.operator, Operator::Minus(_)));
+    let Expression::Binary(inner) = &outer.left else {
+        panic!(
+            "Expected left associative subtraction but got {:?}",
+            outer.left
+        );
+    };
+    let Expression::Binary(int_div) = &inner.left else {
+        panic!("Expected integer division but got {:?}", inner.left);
+    };
+    assert!(matches!(int_div.operator, Operator::TildeSlash(_)));
+    let Expression::Binary(modulo) = &int_div.left else {
+        panic!("Expected modulo but got {:?}", int_div.left);
+    };
+    assert!(matches!(modulo.operator, Operator::Percent(_)));
+    let Expression::Unary(negation) = &modulo.left else {
+        panic!("Expected unary minus but got {:?}", modulo.left);
+    };
+    let Expression::Binary(power) = &negation.right else {
+        panic!("Expected exponent but got {:?}", negation.right);
+    };
+    assert!(matches!(power.operator, Operator::StarStar(_)));
+    assert!(matches!(
+        &power.right,
+        Expression::Binary(right) if matches!(right.operator, Operator::StarStar(_))
+    ));
+}
+
+#[test]
+pub fn test_parse_grouping() {
+    let source = "(1 + 2) * 3;";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("grouping should parse");
+    let Statement::Expression(stmt) = &ast.body[0] else {
+        panic!("Expected expression statement but got {:?}", ast.body[0]);
+    };
+    let Expression::Binary(product) = &stmt.expression else {
+        panic!("Expected binary expression but got {:?}", stmt.expression);
+    };
+    assert!(matches!(&product.left, Expression::Grouping(group) if group.span == Span::new(0, 7)));
+}
+
+#[test]
+pub fn test_parse_bitwise_precedence() {
+    let source = "a | b ^ c & d == e << 1 + 2;";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("bitwise operators should parse");
+    let Statement::Expression(stmt) = &ast.body[0] else {
+        panic!("Expected expression statement but got {:?}", ast.body[0]);
+    };
+    // a | (b ^ (c & (d == (e << (1 + 2)))))
+    let Expression::Binary(or) = &stmt.expression else {
+        panic!("Expected binary expression but got {:?}", stmt.expression);
+    };
+    assert!(matches!(or.operator, Operator::Pipe(_)));
+    let Expression::Binary(xor) = &or.right else {
+        panic!("Expected xor but got {:?}", or.right);
+    };
+    assert!(matches!(xor.operator, Operator::Caret(_)));
+    let Expression::Binary(and) = &xor.right else {
+        panic!("Expected bitwise and but got {:?}", xor.right);
+    };
+    assert!(matches!(and.operator, Operator::Ampersand(_)));
+    let Expression::Binary(equality) = &and.right else {
+        panic!("Expected equality but got {:?}", and.right);
+    };
+    assert!(matches!(equality.operator, Operator::EqualEqual(_)));
+    let Expression::Binary(shift) = &equality.right else {
+        panic!("Expected shift but got {:?}", equality.right);
+    };
+    assert!(matches!(shift.operator, Operator::LessLess(_)));
+    assert!(matches!(
+        &shift.right,
+        Expression::Binary(sum) if matches!(sum.operator, Operator::Plus(_))
+    ));
+}
+
+#[test]
+pub fn test_parse_bitwise_not() {
+    let source = "~x >> 2;";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("bitwise not should parse");
+    let Statement::Expression(stmt) = &ast.body[0] else {
+        panic!("Expected expression statement but got {:?}", ast.body[0]);
+    };
+    let Expression::Binary(shift) = &stmt.expression else {
+        panic!("Expected binary expression but got {:?}", stmt.expression);
+    };
+    assert!(matches!(shift.operator, Operator::GreaterGreater(_)));
+    assert!(matches!(
+        &shift.left,
+        Expression::Unary(not) if matches!(not.operator, Operator::Tilde(_))
+    ));
+}
+
+#[test]
+pub fn test_parse_string_literal_value() {
+    let source = r#"print "tab\there \u{2764}";"#;
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("string literal should parse");
+    let Statement::Print(print) = &ast.body[0] else {
+        panic!("Expected print statement but got {:?}", ast.body[0]);
+    };
+    let Expression::Literal(literal) = &print.value else {
+        panic!("Expected literal but got {:?}", print.value);
+    };
+    let LiteralValue::String(string) = &literal.value else {
+        panic!("Expected string literal but got {:?}", literal.value);
+    };
+    assert_eq!(string.raw, r#""tab\there \u{2764}""#);
+    assert_eq!(string.value, "tab\there \u{2764}");
+}
+
+#[test]
+pub fn test_parse_interpolation() {
+    let source = r#"print "Hello ${name}, you are ${age + 1}";"#;
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("interpolated string should parse");
+    let Statement::Print(print) = &ast.body[0] else {
+        panic!("Expected print statement but got {:?}", ast.body[0]);
+    };
+    let Expression::Interpolation(interpolation) = &print.value else {
+        panic!("Expected interpolation but got {:?}", print.value);
+    };
+    assert_eq!(interpolation.span, Span::new(6, 41));
+    assert_eq!(interpolation.parts.len(), 4);
+    assert!(matches!(
+        &interpolation.parts[0],
+        InterpolationPart::String(part) if part.value == "Hello "
+    ));
+    assert!(matches!(
+        &interpolation.parts[1],
+        InterpolationPart::Expression(Expression::Variable(name)) if name.span == Span::new(15, 19)
+    ));
+    assert!(matches!(
+        &interpolation.parts[2],
+        InterpolationPart::String(part) if part.value == ", you are "
+    ));
+    assert!(matches!(
+        &interpolation.parts[3],
+        InterpolationPart::Expression(Expression::Binary(sum)) if sum.span == Span::new(32, 39)
+    ));
+}
+
+#[test]
+pub fn test_parse_number_literal_value() {
+    let source = "0xFF + 1_000 + 1.5e3;";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("number literals should parse");
+    let Statement::Expression(stmt) = &ast.body[0] else {
+        panic!("Expected expression statement but got {:?}", ast.body[0]);
+    };
+    let mut values = Vec::new();
+    let mut expr = &stmt.expression;
+    while let Expression::Binary(sum) = expr {
+        if let Expression::Literal(literal) = &sum.right {
+            values.push(&literal.value);
+        }
+        expr = &sum.left;
+    }
+    if let Expression::Literal(literal) = expr {
+        values.push(&literal.value);
+    }
+    let values: Vec<(&str, f64)> = values
+        .into_iter()
+        .rev()
+        .map(|value| match value {
+            LiteralValue::Number(number) => (number.raw, number.value),
+            value => panic!("Expected number literal but got {:?}", value),
+        })
+        .collect();
+    assert_eq!(
+        values,
+        vec![("0xFF", 255.0), ("1_000", 1000.0), ("1.5e3", 1500.0)]
+    );
+}
+
+#[test]
+pub fn test_parse_devanagari_number() {
+    let source = "print १२३;";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("devanagari digits should parse");
+    let Statement::Print(print) = &ast.body[0] else {
+        panic!("Expected print statement but got {:?}", ast.body[0]);
+    };
+    assert!(matches!(
+        &print.value,
+        Expression::Literal(literal) if matches!(
+            &literal.value,
+            LiteralValue::Number(number) if number.raw == "१२३" && number.value == 123.0
+        )
+    ));
+}
+
+#[test]
+pub fn test_parse_doc_comments() {
+    let source = "
+		/// The answer.
+		var answer = 42;
+
+		/// Add two numbers.
+		///
+		/// Works with /* block comments */ too.
+		fun add(a, b) {
+			return a + b;
+		}
+
+		/// Not attached to anything.
+		print add(1, 2);
+		var undocumented;
+	";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("doc comments should parse");
+    let Statement::Declaration(answer) = &ast.body[0] else {
+        panic!("Expected declaration but got {:?}", ast.body[0]);
+    };
+    assert_eq!(answer.doc, Some("The answer."));
+    let Statement::Function(add) = &ast.body[1] else {
+        panic!("Expected function but got {:?}", ast.body[1]);
+    };
+    assert_eq!(
+        add.doc,
+        Some("Add two numbers.\n\nWorks with /* block comments */ too.")
+    );
+    let Statement::Declaration(undocumented) = &ast.body[3] else {
+        panic!("Expected declaration but got {:?}", ast.body[3]);
+    };
+    assert_eq!(undocumented.doc, None);
+}
+
+#[test]
+pub fn test_parse_lambda() {
+    let source = "
+		var add = fun (a, b) { return a + b; };
+		fun () { print 1; }();
+	";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("lambdas should parse");
+    let Statement::Declaration(declaration) = &ast.body[0] else {
+        panic!("Expected declaration but got {:?}", ast.body[0]);
+    };
+    let Some(Expression::Lambda(lambda)) = &declaration.value else {
+        panic!("Expected lambda but got {:?}", declaration.value);
+    };
+    assert_eq!(lambda.params.len(), 2);
+    assert!(matches!(lambda.body, Statement::Block(_)));
+    let Statement::Expression(stmt) = &ast.body[1] else {
+        panic!("Expected expression statement but got {:?}", ast.body[1]);
+    };
+    assert!(matches!(
+        &stmt.expression,
+        Expression::Call(call) if matches!(&call.callee, Expression::Lambda(lambda) if lambda.params.is_empty())
+    ));
+}
+
+#[test]
+pub fn test_parse_default_and_rest_parameters() {
+    let source = r#"
+		fun greet(name, greeting = "hi", ...rest) {
+			print greeting;
+		}
+	"#;
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser
+        .parse()
+        .expect("default and rest parameters should parse");
+    let Statement::Function(greet) = &ast.body[0] else {
+        panic!("Expected function but got {:?}", ast.body[0]);
+    };
+    assert_eq!(greet.params.len(), 3);
+    assert!(greet.params[0].default.is_none() && !greet.params[0].rest);
+    assert!(matches!(
+        greet.params[1].default,
+        Some(Expression::Literal(_))
+    ));
+    assert!(greet.params[2].rest);
+    assert_eq!(
+        &source[greet.params[1].span.from..greet.params[1].span.to],
+        "greeting = \"hi\""
+    );
+    assert_eq!(
+        &source[greet.params[2].span.from..greet.params[2].span.to],
+        "...rest"
+    );
+
+    let arity = greet.arity();
+    assert_eq!(arity.to_string(), "at least 1");
+    assert!(!arity.accepts(0));
+    assert!(arity.accepts(1));
+    assert!(arity.accepts(5));
+}
+
+#[test]
+pub fn test_parse_invalid_parameters() {
+    for source in [
+        "fun f(...rest, a) {}",
+        "fun f(...rest = 1) {}",
+        "fun f(a = 1, b) {}",
+    ] {
+        let allocator = Bump::new();
+        let mut parser = Parser::new(source, &allocator);
+        let errors = parser.parse().expect_err(source);
+        assert!(errors[0].span.is_some(), "{}", source);
+    }
+}
+
+#[test]
+pub fn test_parse_list_and_index() {
+    let source = "
+		var xs = [1, [2, 3], ];
+		xs[1][0] = xs[0] + 1;
+		xs[i]++;
+	";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("lists should parse");
+    let Statement::Declaration(declaration) = &ast.body[0] else {
+        panic!("Expected declaration but got {:?}", ast.body[0]);
+    };
+    let Some(Expression::List(list)) = &declaration.value else {
+        panic!("Expected list but got {:?}", declaration.value);
+    };
+    assert_eq!(list.elements.len(), 2);
+    assert!(matches!(&list.elements[1], Expression::List(inner) if inner.elements.len() == 2));
+
+    let Statement::Expression(stmt) = &ast.body[1] else {
+        panic!("Expected expression statement but got {:?}", ast.body[1]);
+    };
+    let Expression::Assignment(assignment) = &stmt.expression else {
+        panic!("Expected assignment but got {:?}", stmt.expression);
+    };
+    let Expression::Index(target) = &assignment.target else {
+        panic!("Expected index target but got {:?}", assignment.target);
+    };
+    assert_eq!(&source[target.span.from..target.span.to], "xs[1][0]");
+    assert!(matches!(&target.object, Expression::Index(_)));
+
+    let Statement::Expression(stmt) = &ast.body[2] else {
+        panic!("Expected expression statement but got {:?}", ast.body[2]);
+    };
+    assert!(matches!(
+        &stmt.expression,
+        Expression::Update(update) if matches!(update.target, Expression::Index(_))
+    ));
+}
+
+#[test]
+pub fn test_parse_map() {
+    let source = r#"
+		var scores = {"a": 1, 2: [3], true: {"nested": nil},};
+		print scores["a"];
+		{ print 1; }
+		({});
+	"#;
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("maps should parse");
+    let Statement::Declaration(declaration) = &ast.body[0] else {
+        panic!("Expected declaration but got {:?}", ast.body[0]);
+    };
+    let Some(Expression::Map(map)) = &declaration.value else {
+        panic!("Expected map but got {:?}", declaration.value);
+    };
+    let keys: Vec<&str> = map
+        .entries
+        .iter()
+        .map(|entry| &source[entry.key.span().from..entry.key.span().to])
+        .collect();
+    assert_eq!(keys, vec![r#""a""#, "2", "true"]);
+    assert!(matches!(map.entries[2].value, Expression::Map(_)));
+    assert!(matches!(
+        &ast.body[1],
+        Statement::Print(print) if matches!(print.value, Expression::Index(_))
+    ));
+    assert!(matches!(ast.body[2], Statement::Block(_)));
+    assert!(matches!(
+        &ast.body[3],
+        Statement::Expression(stmt) if matches!(
+            &stmt.expression,
+            Expression::Grouping(group) if matches!(&group.expression, Expression::Map(map) if map.entries.is_empty())
+        )
+    ));
+}
+
+#[test]
+pub fn test_parse_invalid_map_key() {
+    let source = "var m = {nil: 1};";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let errors = parser.parse().expect_err("nil is not a valid key");
+    assert_eq!(errors[0].span, Some(Span::new(9, 12)));
+}
+
+#[test]
+pub fn test_parse_for_in() {
+    let source = "
+		for (x in xs) print x;
+		for (i in 0..n + 1) {
+			print i;
+		}
+	";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("for-in loops should parse");
+    let Statement::ForIn(over_list) = &ast.body[0] else {
+        panic!("Expected for-in loop but got {:?}", ast.body[0]);
+    };
+    assert_eq!(&source[over_list.variable.from..over_list.variable.to], "x");
+    assert!(matches!(over_list.iterable, Expression::Variable(_)));
+    assert!(matches!(over_list.body, Statement::Print(_)));
+
+    let Statement::ForIn(over_range) = &ast.body[1] else {
+        panic!("Expected for-in loop but got {:?}", ast.body[1]);
+    };
+    let Expression::Range(range) = &over_range.iterable else {
+        panic!("Expected range but got {:?}", over_range.iterable);
+    };
+    assert!(matches!(range.start, Expression::Literal(_)));
+    assert!(matches!(range.end, Expression::Binary(_)));
+    assert_eq!(&source[range.span.from..range.span.to], "0..n + 1");
+}
+
+#[test]
+pub fn test_parse_try_catch_finally() {
+    let source = r#"
+		try {
+			throw "oops";
+		} catch (e) {
+			print e;
+		} finally {
+			print "done";
+		}
+		try { risky(); } finally { cleanup(); }
+	"#;
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("try statements should parse");
+    let Statement::Try(try_) = &ast.body[0] else {
+        panic!("Expected try statement but got {:?}", ast.body[0]);
+    };
+    let Statement::Block(body) = &try_.body else {
+        panic!("Expected block but got {:?}", try_.body);
+    };
+    assert!(matches!(body.body[0], Statement::Throw(_)));
+    let catch = try_.catch.as_ref().expect("catch clause");
+    assert_eq!(&source[catch.param.from..catch.param.to], "e");
+    assert!(try_.finally.is_some());
+    assert_eq!(try_.span.to, try_.finally.as_ref().unwrap().span().to);
+
+    let Statement::Try(try_finally) = &ast.body[1] else {
+        panic!("Expected try statement but got {:?}", ast.body[1]);
+    };
+    assert!(try_finally.catch.is_none());
+    assert!(try_finally.finally.is_some());
+}
+
+#[test]
+pub fn test_parse_try_without_handler() {
+    let source = "try { risky(); } print 1;";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let errors = parser.parse().expect_err("try needs catch or finally");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].span, Some(Span::new(0, 16)));
+}
+
+#[test]
+pub fn test_parse_match() {
+    let source = r#"
+		match (x) {
+			1 => print "one";
+			-2 => print "minus two";
+			"x" => { print "ex"; },
+			n => print n;
+		}
+	"#;
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("match should parse");
+    let Statement::Match(match_) = &ast.body[0] else {
+        panic!("Expected match statement but got {:?}", ast.body[0]);
+    };
+    assert_eq!(match_.arms.len(), 4);
+    assert!(matches!(&match_.arms[0].pattern, Pattern::Literal(_)));
+    let Pattern::Literal(negative) = &match_.arms[1].pattern else {
+        panic!(
+            "Expected literal pattern but got {:?}",
+            match_.arms[1].pattern
+        );
+    };
+    assert!(matches!(
+        &negative.value,
+        LiteralValue::Number(number) if number.raw == "-2" && number.value == -2.0
+    ));
+    assert!(matches!(match_.arms[2].body, Statement::Block(_)));
+    assert!(matches!(
+        &match_.arms[3].pattern,
+        Pattern::Binding(binding) if binding.name == "n"
+    ));
+    let arm = &match_.arms[3];
+    assert_eq!(&source[arm.span.from..arm.span.to], "n => print n");
+}
+
+#[test]
+pub fn test_parse_match_unreachable_arms() {
+    let source = "
+		match (x) {
+			_ => print 0;
+			1 => print 1;
+			n => print n;
+		}
+	";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let errors = parser
+        .parse()
+        .expect_err("arms after a wildcard are unreachable");
+    assert_eq!(errors.len(), 2);
+    let spans: Vec<&str> = errors
+        .iter()
+        .map(|error| error.span.expect("span"))
+        .map(|span| &source[span.from..span.to])
+        .collect();
+    assert_eq!(spans, vec!["1", "n"]);
+    assert_eq!(errors[0].line, 4);
+
+    // A pattern at the start of a line is reported on that line
+    let source = "match (x) {\n_ => print 0;\n1 => print 1;\n}";
+    let mut parser = Parser::new(source, &allocator);
+    let errors = parser
+        .parse()
+        .expect_err("arms after a wildcard are unreachable");
+    assert_eq!(errors[0].line, 3);
+}
+
+#[test]
+pub fn test_parse_const_declaration() {
+    let allocator = Bump::new();
+    let mut parser = Parser::new("const limit = 10;", &allocator);
+    let ast = parser.parse().expect("const declaration should parse");
+    let Statement::Declaration(declaration) = &ast.body[0] else {
+        panic!("Expected declaration but got {:?}", ast.body[0]);
+    };
+    assert!(matches!(declaration.kind, DeclarationKind::Const));
+    assert!(declaration.value.is_some());
+
+    let mut parser = Parser::new("const limit;", &allocator);
+    let errors = parser.parse().expect_err("const needs an initializer");
+    assert!(errors[0].message.contains("Missing initializer"));
+}
+
+#[test]
+pub fn test_reassigning_const() {
+    let source = "
+		const limit = 10;
+		var count = 0;
+		count += 1;
+		{
+			var limit = 1;
+			limit = 2;
+		}
+		fun bump() {
+			limit = 11;
+			limit++;
+		}
+	";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let errors = parser.parse().expect_err("const cannot be reassigned");
+    assert_eq!(errors.len(), 2);
+
+    let span = errors[0].span.expect("span");
+    assert_eq!(&source[span.from..span.to], "limit = 11");
+    assert_eq!(errors[0].line, 10);
+    let label = &errors[0].labels[0];
+    assert_eq!(&source[label.span.from..label.span.to], "const limit = 10;");
+
+    let span = errors[1].span.expect("span");
+    assert_eq!(&source[span.from..span.to], "limit++");
+}
+
+#[test]
+pub fn test_reassigning_const_declared_later() {
+    let source = "
+		fun reset() {
+			limit = 0;
+		}
+		{
+			fun bump() { step += 1; }
+			const step = 1;
+		}
+		const limit = 10;
+	";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let errors = parser.parse().expect_err("const cannot be reassigned");
+    let spans: Vec<&str> = errors
+        .iter()
+        .map(|error| error.span.expect("span"))
+        .map(|span| &source[span.from..span.to])
+        .collect();
+    assert_eq!(spans, vec!["limit = 0", "step += 1"]);
+    let label = &errors[0].labels[0];
+    assert_eq!(&source[label.span.from..label.span.to], "const limit = 10;");
+}
+
+#[test]
+pub fn test_let_temporal_dead_zone() {
+    let source = "
+		print early;
+		let early = 1;
+		let itself = itself;
+		fun later() {
+			return after;
+		}
+		let after = 2;
+		print after;
+	";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let errors = parser
+        .parse()
+        .expect_err("let is read before its declaration");
+    assert_eq!(errors.len(), 2);
+    let spans: Vec<&str> = errors
+        .iter()
+        .map(|error| error.span.expect("span"))
+        .map(|span| &source[span.from..span.to])
+        .collect();
+    assert_eq!(spans, vec!["early", "itself"]);
+    let label = &errors[0].labels[0];
+    assert_eq!(&source[label.span.from..label.span.to], "let early = 1;");
+}
+
+#[test]
+pub fn test_var_scope_option() {
+    let source = "
+		const total = 0;
+		fun count() {
+			total = 1;
+			{
+				var total = 2;
+			}
+		}
+	";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let errors = parser
+        .parse()
+        .expect_err("block-scoped var leaves total const");
+    assert_eq!(errors.len(), 1);
+
+    let mut parser = Parser::new(source, &allocator).with_options(LanguageOptions {
+        var_scope: crate::options::VarScope::Function,
+        ..LanguageOptions::default()
+    });
+    assert!(
+        parser.parse().is_ok(),
+        "function-scoped var hoists over the const"
+    );
+}
+
+#[test]
+pub fn test_parse_nil_coalescing_precedence() {
+    let allocator = Bump::new();
+    let mut parser = Parser::new("a ?? b or c;", &allocator);
+    let ast = parser.parse().expect("nil coalescing should parse");
+    let Statement::Expression(statement) = &ast.body[0] else {
+        panic!("Expected expression statement but got {:?}", ast.body[0]);
+    };
+    let Expression::Logical(logical) = &statement.expression else {
+        panic!(
+            "Expected logical expression but got {:?}",
+            statement.expression
+        );
+    };
+    assert!(matches!(logical.operator, Operator::QuestionQuestion(_)));
+    assert!(matches!(
+        &logical.right,
+        Expression::Logical(right) if matches!(right.operator, Operator::Or(_))
+    ));
+}
+
+#[test]
+pub fn test_parse_optional_chaining() {
+    let source = "user?.profile.greet?.(1);";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("optional chaining should parse");
+    let Statement::Expression(statement) = &ast.body[0] else {
+        panic!("Expected expression statement but got {:?}", ast.body[0]);
+    };
+    let Expression::Call(call) = &statement.expression else {
+        panic!("Expected call but got {:?}", statement.expression);
+    };
+    assert!(call.optional);
+    let Expression::Get(greet) = &call.callee else {
+        panic!("Expected get but got {:?}", call.callee);
+    };
+    assert!(greet.name == "greet" && !greet.optional);
+    let Expression::Get(profile) = &greet.object else {
+        panic!("Expected get but got {:?}", greet.object);
+    };
+    assert!(profile.name == "profile" && profile.optional);
+    assert_eq!(&source[profile.span.from..profile.span.to], "user?.profile");
+}
+
+#[test]
+pub fn test_parse_field_assignment() {
+    let allocator = Bump::new();
+    let mut parser = Parser::new("point.x = 1;", &allocator);
+    let ast = parser.parse().expect("field assignment should parse");
+    let Statement::Expression(statement) = &ast.body[0] else {
+        panic!("Expected expression statement but got {:?}", ast.body[0]);
+    };
+    assert!(matches!(
+        &statement.expression,
+        Expression::Assignment(assignment) if matches!(assignment.target, Expression::Get(_))
+    ));
+
+    for source in [
+        "point?.x = 1;",
+        "a?.b.c = 1;",
+        "a?.b[0] = 1;",
+        "a?.().b += 1;",
+    ] {
+        let mut parser = Parser::new(source, &allocator);
+        let errors = parser
+            .parse()
+            .expect_err("optional chains cannot be assigned");
+        assert!(
+            errors[0].message.contains("Invalid assignment target"),
+            "{}",
+            source
+        );
+    }
+
+    let mut parser = Parser::new("a.b?.c(x).d = 1; (a?.b).c = 2;", &allocator);
+    assert!(parser.parse().is_err());
+    let mut parser = Parser::new("a.b[0].c = 1; (a?.b).c = 2;", &allocator);
+    parser
+        .parse()
+        .expect("a parenthesized chain or one without ?. can be assigned");
+}
+
+#[test]
+pub fn test_parse_destructuring_declarations() {
+    let source = "
+		var [first, [second, _]] = pairs;
+		const {x, y: [low, high]} = point;
+	";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("destructuring should parse");
+
+    let Statement::Declaration(list) = &ast.body[0] else {
+        panic!("Expected declaration but got {:?}", ast.body[0]);
+    };
+    let Pattern::List(pattern) = &list.target else {
+        panic!("Expected list pattern but got {:?}", list.target);
+    };
+    assert_eq!(pattern.elements.len(), 2);
+    assert!(matches!(&pattern.elements[1], Pattern::List(_)));
+    let names: Vec<&str> = list.target.bindings().iter().map(|b| b.name).collect();
+    assert_eq!(names, vec!["first", "second"]);
+
+    let Statement::Declaration(map) = &ast.body[1] else {
+        panic!("Expected declaration but got {:?}", ast.body[1]);
+    };
+    let Pattern::Map(pattern) = &map.target else {
+        panic!("Expected map pattern but got {:?}", map.target);
+    };
+    assert_eq!(pattern.entries[0].key, "x");
+    assert_eq!(pattern.entries[1].key, "y");
+    let span = pattern.entries[1].span;
+    assert_eq!(&source[span.from..span.to], "y: [low, high]");
+    let names: Vec<&str> = map.target.bindings().iter().map(|b| b.name).collect();
+    assert_eq!(names, vec!["x", "low", "high"]);
+}
+
+#[test]
+pub fn test_invalid_destructuring_declarations() {
+    let allocator = Bump::new();
+
+    let mut parser = Parser::new("var [a, b];", &allocator);
+    let errors = parser.parse().expect_err("destructuring needs a value");
+    assert!(errors[0].message.contains("Missing initializer"));
+
+    let mut parser = Parser::new("var [a, 1] = pair;", &allocator);
+    let errors = parser
+        .parse()
+        .expect_err("declarations can't match literals");
+    assert!(errors[0].message.contains("can't be used in a declaration"));
+
+    let source = "const [a, b] = pair; b = 1;";
+    let mut parser = Parser::new(source, &allocator);
+    let errors = parser
+        .parse()
+        .expect_err("destructured consts are constant");
+    let label = &errors[0].labels[0];
+    assert_eq!(
+        &source[label.span.from..label.span.to],
+        "const [a, b] = pair;"
+    );
+}
+
+#[test]
+pub fn test_parse_generators() {
+    let source = "
+		fun counter(limit) {
+			var i = 0;
+			while (i < limit) {
+				var reset = yield i;
+				i++;
+			}
+			var inner = fun() { return 1; };
+		}
+		fun plain() {
+			var gen = fun() { yield; };
+		}
+	";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("generators should parse");
+    let Statement::Function(counter) = &ast.body[0] else {
+        panic!("Expected function but got {:?}", ast.body[0]);
+    };
+    assert!(counter.is_generator);
+    let Statement::Function(plain) = &ast.body[1] else {
+        panic!("Expected function but got {:?}", ast.body[1]);
+    };
+    assert!(
+        !plain.is_generator,
+        "yield in a nested lambda doesn't count"
+    );
+    let Statement::Block(body) = &plain.body else {
+        panic!("Expected block but got {:?}", plain.body);
+    };
+    let Statement::Declaration(gen) = &body.body[0] else {
+        panic!("Expected declaration but got {:?}", body.body[0]);
+    };
+    assert!(matches!(
+        &gen.value,
+        Some(Expression::Lambda(lambda)) if lambda.is_generator
+    ));
+}
+
+#[test]
+pub fn test_yield_outside_function() {
+    let allocator = Bump::new();
+    let mut parser = Parser::new("yield 1;", &allocator);
+    let errors = parser.parse().expect_err("yield needs a function");
+    assert!(errors[0].message.contains("'yield' outside of a function"));
+
+    let source = "fun gen(a = yield 1) { }";
+    let mut parser = Parser::new(source, &allocator);
+    let errors = parser.parse().expect_err("defaults can't yield");
+    assert_eq!(
+        errors[0].message,
+        "Syntax Error: 'yield' can't be used in a parameter default"
+    );
+    assert_eq!(errors[0].span, Some(Span::new(12, 17)));
+
+    let source = "fun outer() { var f = fun(b = fun() { yield 1; }) { }; }";
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("a lambda in a default can yield");
+    let Statement::Function(outer) = &ast.body[0] else {
+        panic!("Expected function but got {:?}", ast.body[0]);
+    };
+    assert!(!outer.is_generator);
+}
+
+#[test]
+pub fn test_parse_enum() {
+    let source = "
+		/// Primary colours
+		enum Color { Red, Green, Blue, }
+		match (color) {
+			Color.Red => print \"warm\";
+			_ => print \"cool\";
+		}
+	";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("enum should parse");
+    let Statement::Enum(color) = &ast.body[0] else {
+        panic!("Expected enum but got {:?}", ast.body[0]);
+    };
+    assert_eq!(color.doc, Some("Primary colours"));
+    let variants: Vec<&str> = color.variants.iter().map(|v| v.name).collect();
+    assert_eq!(variants, vec!["Red", "Green", "Blue"]);
+
+    let Statement::Match(match_) = &ast.body[1] else {
+        panic!("Expected match but got {:?}", ast.body[1]);
+    };
+    assert!(matches!(
+        &match_.arms[0].pattern,
+        Pattern::Variant(variant) if variant.enum_name == "Color" && variant.variant == "Red"
+    ));
+}
+
+#[test]
+pub fn test_enum_errors() {
+    let source = "enum Color { Red, Green, Red }";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let errors = parser.parse().expect_err("variants must be unique");
+    assert_eq!(errors.len(), 1);
+    let span = errors[0].span.expect("span");
+    assert_eq!(span.from, source.rfind("Red").unwrap());
+    assert_eq!(errors[0].labels[0].span.from, source.find("Red").unwrap());
+
+    let mut parser = Parser::new("enum Color {\n  Red,\n  Red\n}", &allocator);
+    let errors = parser.parse().expect_err("variants must be unique");
+    assert_eq!(errors[0].line, 3);
+
+    let mut parser = Parser::new("enum Color { Red } Color = 1;", &allocator);
+    let errors = parser.parse().expect_err("enums can't be reassigned");
+    assert!(errors[0].message.contains("Cannot assign to constant"));
+}
+
+#[test]
+pub fn test_parse_class() {
+    let source = "
+		class Circle < Shape {
+			area { return 3.14 * this.radius * this.radius; }
+			set radius(value) { this.r = value; }
+			class unit() { return Circle(1); }
+			describe() { return super.describe(); }
+			set(key, value) { }
+		}
+	";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("class should parse");
+    let Statement::Class(class) = &ast.body[0] else {
+        panic!("Expected class but got {:?}", ast.body[0]);
+    };
+    assert!(class.superclass.is_some());
+    let kinds: Vec<MethodKind> = class.methods.iter().map(|method| method.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            MethodKind::Getter,
+            MethodKind::Setter,
+            MethodKind::Static,
+            MethodKind::Method,
+            MethodKind::Method,
+        ]
+    );
+    assert!(class.methods[0].params.is_empty());
+    let area = class.methods[0].span;
+    assert!(source[area.from..area.to].starts_with("area {"));
+    let set = &class.methods[4].name;
+    assert_eq!(&source[set.from..set.to], "set");
+}
+
+#[test]
+pub fn test_class_errors() {
+    let allocator = Bump::new();
+
+    let mut parser = Parser::new("class A { set x(a, b) { } }", &allocator);
+    let errors = parser.parse().expect_err("setters take one parameter");
+    assert!(errors[0].message.contains("exactly one parameter"));
+
+    let source = "
+		print this;
+		class A {
+			class make() { return this; }
+			parent() { return super.parent(); }
+		}
+		class B < B { }
+	";
+    let mut parser = Parser::new(source, &allocator);
+    let errors = parser.parse().expect_err("this and super need a class");
+    let messages: Vec<&str> = errors.iter().map(|error| error.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "Can't use 'this' outside of a class",
+            "Can't use 'this' in a static method",
+            "Can't use 'super' in a class with no superclass",
+            "A class can't inherit from itself",
+        ]
+    );
+}
+
+#[test]
+pub fn test_lexical_declaration_as_body() {
+    let allocator = Bump::new();
+    for source in [
+        "if (c) let y = 1;",
+        "if (c) print 1; else const y = 1;",
+        "while (c) let y = 1;",
+        "for (;;) const y = 1;",
+        "for (x in xs) let y = x;",
+    ] {
+        let mut parser = Parser::new(source, &allocator);
+        let errors = parser.parse().expect_err(source);
+        assert!(errors[0].message.contains("must be inside a block"));
+        let span = errors[0].span.expect("span");
+        assert!(
+            source[span.from..span.to].starts_with("let y")
+                || source[span.from..].starts_with("const y")
+        );
+    }
+
+    let mut parser = Parser::new("if (c) var y = 1; if (c) { let z = 1; }", &allocator);
+    assert!(parser.parse().is_ok());
+}
+
+#[test]
+pub fn test_numeral_scripts_option() {
+    let allocator = Bump::new();
+    let mut parser = Parser::new("print ١٢;", &allocator);
+    assert!(
+        parser.parse().is_err(),
+        "Arabic-Indic digits are off by default"
+    );
+
+    let mut parser = Parser::new("print ١٢;", &allocator).with_options(LanguageOptions {
+        numeral_scripts: vec![crate::lexer::number::NumeralScript::ArabicIndic],
+        ..LanguageOptions::default()
+    });
+    let ast = parser.parse().expect("Arabic-Indic digits are enabled");
+    let Statement::Print(print) = &ast.body[0] else {
+        panic!("Expected print but got {:?}", ast.body[0]);
+    };
+    assert!(matches!(
+        &print.value,
+        Expression::Literal(literal)
+            if matches!(literal.value, LiteralValue::Number(ref number) if number.value == 12.0)
+    ));
+}
+
+#[test]
+pub fn test_call_arity() {
+    let source = "
+		fun add(a, b) { return a + b; }
+		const greet = fun(name, greeting = \"hi\") { };
+		fun log(level, ...parts) { }
+		add(1, 2);
+		add(1);
+		greet(\"x\");
+		greet();
+		log(1, 2, 3);
+		log();
+		fun one(a) { }
+		one();
+	";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let errors = parser
+        .parse()
+        .expect_err("calls with the wrong argument count");
+    let messages: Vec<&str> = errors.iter().map(|error| error.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "'add' expects 2 arguments but got 1",
+            "'greet' expects 1 to 2 arguments but got 0",
+            "'log' expects at least 1 argument but got 0",
+            "'one' expects 1 argument but got 0",
+        ]
+    );
+    let span = errors[0].span.expect("span");
+    assert_eq!(&source[span.from..span.to], "add(1)");
+    let label = &errors[0].labels[0];
+    assert!(source[label.span.from..label.span.to].starts_with("fun add(a, b)"));
+
+    // Calls to a function that is reassigned or redeclared anywhere, even
+    // after the call, can't be checked
+    let source = "
+		fun f(a) { }
+		fun g() { f(); }
+		f = fun() { };
+		f();
+		fun h(a) { }
+		fun h() { }
+		h();
+	";
+    let mut parser = Parser::new(source, &allocator);
+    parser.parse().expect("reassigned functions aren't checked");
+}
+
+#[test]
+pub fn test_underscore_declaration_is_binding() {
+    let source = "var _ = 1; var [_, b] = p; match (x) { _ => {} }";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("valid program");
+
+    let Statement::Declaration(declaration) = &ast.body[0] else {
+        panic!("expected a declaration");
+    };
+    assert!(matches!(&declaration.target, Pattern::Binding(binding) if binding.name == "_"));
+
+    let Statement::Declaration(declaration) = &ast.body[1] else {
+        panic!("expected a declaration");
+    };
+    let Pattern::List(list) = &declaration.target else {
+        panic!("expected a list pattern");
+    };
+    assert!(matches!(list.elements[0], Pattern::Wildcard(_)));
+
+    let Statement::Match(statement) = &ast.body[2] else {
+        panic!("expected a match statement");
+    };
+    assert!(matches!(statement.arms[0].pattern, Pattern::Wildcard(_)));
+}
+
+#[test]
+pub fn test_duplicate_pattern_bindings() {
+    let source = "var [a, a] = p;\nvar {x, y: [x]} = p;\nvar [_, _] = p;";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let errors = parser.parse().expect_err("duplicate bindings");
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!(
+        errors[0].message,
+        "Syntax Error: Duplicate binding a in pattern"
+    );
+    assert_eq!(errors[0].span, Some(Span::new(8, 9)));
+    assert_eq!(errors[0].labels[0].span, Span::new(5, 6));
+    assert_eq!(
+        errors[1].message,
+        "Syntax Error: Duplicate binding x in pattern"
+    );
+    assert_eq!(errors[1].line, 2);
+}
+
+#[test]
+pub fn test_variant_patterns_are_checked() {
+    let source = "
+		fun paint(color) {
+			match (color) {
+				Color.Red => print \"warm\";
+				Color.Blue => print \"cold\";
+				Shade.Dark => print \"dark\";
+				[Color.Green, _] => print \"pair\";
+			}
+		}
+		enum Color { Red, Green }
+		var Shade = 1;
+	";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let errors = parser.parse().expect_err("unknown enum and variant");
+    let messages: Vec<&str> = errors.iter().map(|error| error.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "'Blue' is not a variant of enum 'Color'",
+            "'Shade' is not an enum in scope",
+        ]
+    );
+    let span = errors[0].span.expect("span");
+    assert_eq!(&source[span.from..span.to], "Color.Blue");
+    assert_eq!(errors[0].line, 5);
+}
+
+#[test]
+pub fn test_initializer() {
+    let source = "
+		class Point {
+			init(x, y) {
+				this.x = x;
+				if (x == nil) return;
+				var scale = fun(v) { return v * 2; };
+				return this;
+			}
+			class init() { return 1; }
+		}
+	";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let errors = parser
+        .parse()
+        .expect_err("initializers can't return a value");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].message,
+        "Can't return a value from an initializer"
+    );
+    let span = errors[0].span.expect("span");
+    assert_eq!(&source[span.from..span.to], "return this;");
+
+    let mut parser = Parser::new("class Point { init(x) { } class init() { } }", &allocator);
+    let ast = parser.parse().expect("class should parse");
+    let Statement::Class(class) = &ast.body[0] else {
+        panic!("Expected class but got {:?}", ast.body[0]);
+    };
+    let kinds: Vec<MethodKind> = class.methods.iter().map(|method| method.kind).collect();
+    assert_eq!(kinds, vec![MethodKind::Initializer, MethodKind::Static]);
+}
+
+#[test]
+pub fn test_map_expression_statement() {
+    let source = r#"
+		{"a": 1}.size;
+		{x: 1, y: 2};
+		{} == other;
+		{ print 1; }
+		{ }
+		{ x; }
+	"#;
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let ast = parser.parse().expect("maps and blocks should parse");
+    let kinds: Vec<bool> = ast
+        .body
+        .iter()
+        .map(|statement| matches!(statement, Statement::Expression(_)))
+        .collect();
+    assert_eq!(kinds, vec![true, true, true, false, false, false]);
+    assert!(matches!(
+        &ast.body[0],
+        Statement::Expression(stmt) if matches!(&stmt.expression, Expression::Get(_))
+    ));
+
+    // With no empty statement, the `;` after an empty block is an error
+    // rather than turning the block into a map
+    let mut parser = Parser::new("if (x) {};", &allocator);
+    let errors = parser.parse().expect_err("stray semicolon");
+    assert_eq!(
+        errors[0].message,
+        "Syntax Error: Expression Expected but got ;"
+    );
+}
+
+#[test]
+pub fn test_parse_for_lexical_initializer() {
+    let allocator = Bump::new();
+    for (source, kind) in [
+        ("for (let i = 0; i < 3; i++) print i;", DeclarationKind::Let),
+        ("for (const n = 3; n > 0;) print n;", DeclarationKind::Const),
+    ] {
+        let mut parser = Parser::new(source, &allocator);
+        let ast = parser.parse().expect("lexical for initializer");
+        let Statement::For(for_) = &ast.body[0] else {
+            panic!("Expected for but got {:?}", ast.body[0]);
+        };
+        assert!(matches!(
+            &for_.initializer,
+            Some(Statement::Declaration(declaration)) if declaration.kind == kind
+        ));
+    }
+}
+
+#[test]
+pub fn test_for_let_is_scoped_to_loop() {
+    // The loop's `i` shadows the const, and only the write after the loop
+    // reaches the const again
+    let source = "const i = 0;\nfor (let i = 0; i < 3; i++) print i;\ni = 5;";
+    let allocator = Bump::new();
+    let mut parser = Parser::new(source, &allocator);
+    let errors = parser.parse().expect_err("the const is back in scope");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message, "Cannot assign to constant 'i'");
+    assert_eq!(errors[0].line, 3);
+}
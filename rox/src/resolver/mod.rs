@@ -0,0 +1,670 @@
+use crate::{
+    ast::{
+        expression::{Expression, InterpolationPart},
+        pattern::Pattern,
+        span::Span,
+        statement::{Arity, DeclarationKind, Enum, MethodKind, Parameter, Statement},
+        Ast,
+    },
+    lexer::token::Token,
+    lox_error::LoxError,
+    options::{LanguageOptions, VarScope},
+};
+use std::collections::{HashMap, HashSet};
+
+/// What the resolver knows about a name in scope
+#[derive(Debug, Clone)]
+struct Binding<'alloc> {
+    kind: DeclarationKind,
+    /// Span of the whole declaration, used to point back at it in errors
+    span: Span,
+    /// `false` while a `let` or `const` is in its temporal dead zone
+    initialized: bool,
+    /// Set for function declarations and consts bound to a lambda, whose
+    /// calls can be checked against it
+    arity: Option<Arity>,
+    /// Set for enums, checked against `Color.Red` patterns
+    variants: Option<Vec<&'alloc str>>,
+}
+
+struct Scope<'alloc> {
+    bindings: HashMap<&'alloc str, Binding<'alloc>>,
+    /// How many functions deep the scope is, `0` at the top level
+    function_depth: usize,
+}
+
+/// The class whose methods are being resolved
+#[derive(Debug, Clone, Copy)]
+struct ClassContext {
+    has_superclass: bool,
+    /// `this` is not bound in a static method
+    in_static_method: bool,
+}
+
+/// The kind of function whose body is being resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FunctionKind {
+    Function,
+    /// Returns `this`, so it can't return a value of its own
+    Initializer,
+}
+
+/// Semantic checks that need to know which declaration a name refers to.
+/// Runs over a syntactically valid AST after parsing.
+pub struct Resolver<'alloc> {
+    source: &'alloc str,
+    options: LanguageOptions,
+    scopes: Vec<Scope<'alloc>>,
+    function_depth: usize,
+    /// Enclosing functions, innermost last
+    functions: Vec<FunctionKind>,
+    /// Enclosing classes, innermost last
+    classes: Vec<ClassContext>,
+    /// Start of the declaration of every binding that is assigned or
+    /// redeclared anywhere, found by a first pass. Calls to a function
+    /// declaration in here can't be checked against its parameters.
+    reassigned: HashSet<usize>,
+    errors: Vec<LoxError>,
+}
+
+impl<'alloc> Resolver<'alloc> {
+    pub fn new(source: &'alloc str, options: LanguageOptions) -> Self {
+        Self {
+            source,
+            options,
+            scopes: vec![Scope {
+                bindings: HashMap::new(),
+                function_depth: 0,
+            }],
+            function_depth: 0,
+            functions: Vec::new(),
+            classes: Vec::new(),
+            reassigned: HashSet::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn resolve(self, ast: &Ast<'alloc>) -> Vec<LoxError> {
+        let reassigned = Resolver::new(self.source, self.options.clone())
+            .resolve_body(ast)
+            .reassigned;
+        Self { reassigned, ..self }.resolve_body(ast).errors
+    }
+
+    fn resolve_body(mut self, ast: &Ast<'alloc>) -> Self {
+        if self.options.var_scope == VarScope::Function {
+            for statement in ast.body.iter() {
+                self.hoist_vars(statement);
+            }
+        }
+        self.hoist_lexical(&ast.body);
+        for statement in ast.body.iter() {
+            self.resolve_statement(statement);
+        }
+        self
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(Scope {
+            bindings: HashMap::new(),
+            function_depth: self.function_depth,
+        });
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, name: &'alloc str, kind: DeclarationKind, span: Span) {
+        let scope = self
+            .scopes
+            .last_mut()
+            .expect("the global scope is never popped");
+        // Hoisting declares the same binding again when it's reached
+        if let Some(previous) = scope.bindings.get(name) {
+            if previous.span.from != span.from {
+                self.reassigned.insert(previous.span.from);
+            }
+        }
+        scope.bindings.insert(
+            name,
+            Binding {
+                kind,
+                span,
+                initialized: true,
+                arity: None,
+                variants: None,
+            },
+        );
+    }
+
+    /// Bring the `let`, `const` and `enum` declarations of a block into
+    /// scope before any of its statements run, uninitialized until their
+    /// declaration is reached
+    fn hoist_lexical(&mut self, statements: &[Statement<'alloc>]) {
+        for statement in statements {
+            match statement {
+                Statement::Declaration(declaration) => {
+                    if let DeclarationKind::Const | DeclarationKind::Let = declaration.kind {
+                        for binding in declaration.target.bindings() {
+                            self.declare(binding.name, declaration.kind, declaration.span);
+                            self.mark_uninitialized(binding.name);
+                        }
+                    }
+                }
+                Statement::Enum(enum_) => {
+                    self.declare_enum(enum_);
+                    let name = &self.source[enum_.name.from..enum_.name.to];
+                    self.mark_uninitialized(name);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// The namespace can't be reassigned, like a const
+    fn declare_enum(&mut self, enum_: &Enum<'alloc>) {
+        let name = &self.source[enum_.name.from..enum_.name.to];
+        self.declare(name, DeclarationKind::Const, enum_.span);
+        if let Some(binding) = self
+            .scopes
+            .last_mut()
+            .and_then(|scope| scope.bindings.get_mut(name))
+        {
+            binding.variants = Some(enum_.variants.iter().map(|variant| variant.name).collect());
+        }
+    }
+
+    /// Declare every `var` of a function body up front, however deeply
+    /// nested in blocks and loops, stopping at nested functions which
+    /// hoist their own. Only used with function-scoped `var`.
+    fn hoist_vars(&mut self, statement: &Statement<'alloc>) {
+        match statement {
+            Statement::Declaration(declaration) => {
+                if let DeclarationKind::Var = declaration.kind {
+                    for binding in declaration.target.bindings() {
+                        self.declare(binding.name, declaration.kind, declaration.span);
+                    }
+                }
+            }
+            Statement::Block(block) => {
+                for statement in block.body.iter() {
+                    self.hoist_vars(statement);
+                }
+            }
+            Statement::For(for_) => {
+                if let Some(initializer) = &for_.initializer {
+                    self.hoist_vars(initializer);
+                }
+                self.hoist_vars(&for_.body);
+            }
+            Statement::ForIn(for_in) => self.hoist_vars(&for_in.body),
+            Statement::If(if_) => {
+                self.hoist_vars(&if_.body);
+                if let Some(else_branch) = &if_.else_branch {
+                    self.hoist_vars(else_branch);
+                }
+            }
+            Statement::Match(match_) => {
+                for arm in match_.arms.iter() {
+                    self.hoist_vars(&arm.body);
+                }
+            }
+            Statement::Try(try_) => {
+                self.hoist_vars(&try_.body);
+                if let Some(catch) = &try_.catch {
+                    self.hoist_vars(&catch.body);
+                }
+                if let Some(finally) = &try_.finally {
+                    self.hoist_vars(finally);
+                }
+            }
+            Statement::While(while_) => self.hoist_vars(&while_.body),
+            Statement::Class(_)
+            | Statement::Enum(_)
+            | Statement::Expression(_)
+            | Statement::Function(_)
+            | Statement::Print(_)
+            | Statement::Return(_)
+            | Statement::Throw(_) => {}
+        }
+    }
+
+    fn mark_uninitialized(&mut self, name: &str) {
+        if let Some(binding) = self
+            .scopes
+            .last_mut()
+            .and_then(|scope| scope.bindings.get_mut(name))
+        {
+            binding.initialized = false;
+        }
+    }
+
+    fn set_arity(&mut self, name: &str, arity: Arity) {
+        if let Some(binding) = self
+            .scopes
+            .last_mut()
+            .and_then(|scope| scope.bindings.get_mut(name))
+        {
+            binding.arity = Some(arity);
+        }
+    }
+
+    fn declare_token(&mut self, token: Token, kind: DeclarationKind) {
+        let name = &self.source[token.from..token.to];
+        self.declare(name, kind, Span::new(token.from, token.to));
+    }
+
+    /// The binding a name refers to and the function depth of its scope
+    fn lookup(&self, name: &str) -> Option<(&Binding<'alloc>, usize)> {
+        self.scopes.iter().rev().find_map(|scope| {
+            scope
+                .bindings
+                .get(name)
+                .map(|binding| (binding, scope.function_depth))
+        })
+    }
+
+    fn resolve_statement(&mut self, statement: &Statement<'alloc>) {
+        match statement {
+            Statement::Block(block) => {
+                self.begin_scope();
+                self.hoist_lexical(&block.body);
+                for statement in block.body.iter() {
+                    self.resolve_statement(statement);
+                }
+                self.end_scope();
+            }
+            Statement::Declaration(declaration) => {
+                if let Some(value) = &declaration.value {
+                    self.resolve_expression(value);
+                }
+                let hoisted = matches!(declaration.kind, DeclarationKind::Var)
+                    && self.options.var_scope == VarScope::Function;
+                if !hoisted {
+                    for binding in declaration.target.bindings() {
+                        self.declare(binding.name, declaration.kind, declaration.span);
+                    }
+                }
+                // Only a const is sure to still hold the lambda when called
+                if let (
+                    DeclarationKind::Const,
+                    Pattern::Binding(binding),
+                    Some(Expression::Lambda(lambda)),
+                ) = (declaration.kind, &declaration.target, &declaration.value)
+                {
+                    self.set_arity(binding.name, lambda.arity());
+                }
+            }
+            Statement::Class(class) => {
+                self.declare_token(class.name, DeclarationKind::Var);
+                if let Some(superclass) = class.superclass {
+                    let name = &self.source[superclass.from..superclass.to];
+                    let span = Span::new(superclass.from, superclass.to);
+                    if name == &self.source[class.name.from..class.name.to] {
+                        self.errors.push(
+                            LoxError::new(
+                                span.line(self.source),
+                                "A class can't inherit from itself".to_string(),
+                            )
+                            .with_span(span),
+                        );
+                    }
+                    self.check_initialized(name, span);
+                }
+
+                self.classes.push(ClassContext {
+                    has_superclass: class.superclass.is_some(),
+                    in_static_method: false,
+                });
+                for method in class.methods.iter() {
+                    if let Some(context) = self.classes.last_mut() {
+                        context.in_static_method = method.kind == MethodKind::Static;
+                    }
+                    let kind = match method.kind {
+                        MethodKind::Initializer => FunctionKind::Initializer,
+                        _ => FunctionKind::Function,
+                    };
+                    self.resolve_function(&method.params, &method.body, kind);
+                }
+                self.classes.pop();
+            }
+            Statement::Enum(enum_) => self.declare_enum(enum_),
+            Statement::Expression(expression) => self.resolve_expression(&expression.expression),
+            Statement::For(for_) => {
+                self.begin_scope();
+                if let Some(initializer) = &for_.initializer {
+                    self.resolve_statement(initializer);
+                }
+                if let Some(condition) = &for_.condition {
+                    self.resolve_expression(condition);
+                }
+                if let Some(increment) = &for_.increment {
+                    self.resolve_expression(increment);
+                }
+                self.resolve_statement(&for_.body);
+                self.end_scope();
+            }
+            Statement::ForIn(for_in) => {
+                self.resolve_expression(&for_in.iterable);
+                self.begin_scope();
+                self.declare_token(for_in.variable, DeclarationKind::Var);
+                self.resolve_statement(&for_in.body);
+                self.end_scope();
+            }
+            Statement::Function(function) => {
+                let name = &self.source[function.name.from..function.name.to];
+                self.declare(name, DeclarationKind::Var, function.span);
+                if !self.reassigned.contains(&function.span.from) {
+                    self.set_arity(name, function.arity());
+                }
+                self.resolve_function(&function.params, &function.body, FunctionKind::Function);
+            }
+            Statement::If(if_) => {
+                self.resolve_expression(&if_.condition);
+                self.resolve_statement(&if_.body);
+                if let Some(else_branch) = &if_.else_branch {
+                    self.resolve_statement(else_branch);
+                }
+            }
+            Statement::Match(match_) => {
+                self.resolve_expression(&match_.subject);
+                for arm in match_.arms.iter() {
+                    self.check_variant_patterns(&arm.pattern);
+                    self.begin_scope();
+                    for binding in arm.pattern.bindings() {
+                        self.declare(binding.name, DeclarationKind::Var, binding.span);
+                    }
+                    self.resolve_statement(&arm.body);
+                    self.end_scope();
+                }
+            }
+            Statement::Print(print) => self.resolve_expression(&print.value),
+            Statement::Return(return_) => {
+                if let Some(value) = &return_.value {
+                    if self.functions.last() == Some(&FunctionKind::Initializer) {
+                        self.errors.push(
+                            LoxError::new(
+                                return_.span.line(self.source),
+                                "Can't return a value from an initializer".to_string(),
+                            )
+                            .with_span(return_.span),
+                        );
+                    }
+                    self.resolve_expression(value);
+                }
+            }
+            Statement::Throw(throw) => self.resolve_expression(&throw.value),
+            Statement::Try(try_) => {
+                self.resolve_statement(&try_.body);
+                if let Some(catch) = &try_.catch {
+                    self.begin_scope();
+                    self.declare_token(catch.param, DeclarationKind::Var);
+                    self.resolve_statement(&catch.body);
+                    self.end_scope();
+                }
+                if let Some(finally) = &try_.finally {
+                    self.resolve_statement(finally);
+                }
+            }
+            Statement::While(while_) => {
+                self.resolve_expression(&while_.condition);
+                self.resolve_statement(&while_.body);
+            }
+        }
+    }
+
+    fn resolve_function(
+        &mut self,
+        params: &[Parameter<'alloc>],
+        body: &Statement<'alloc>,
+        kind: FunctionKind,
+    ) {
+        self.functions.push(kind);
+        self.function_depth += 1;
+        self.begin_scope();
+        for param in params {
+            if let Some(default) = &param.default {
+                self.resolve_expression(default);
+            }
+            self.declare_token(param.name, DeclarationKind::Var);
+        }
+        if self.options.var_scope == VarScope::Function {
+            self.hoist_vars(body);
+        }
+        self.resolve_statement(body);
+        self.end_scope();
+        self.function_depth -= 1;
+        self.functions.pop();
+    }
+
+    fn resolve_expression(&mut self, expression: &Expression<'alloc>) {
+        match expression {
+            Expression::Assignment(assignment) => {
+                self.check_assignment_target(&assignment.target, assignment.span);
+                self.resolve_expression(&assignment.target);
+                self.resolve_expression(&assignment.value);
+            }
+            Expression::Binary(binary) => {
+                self.resolve_expression(&binary.left);
+                self.resolve_expression(&binary.right);
+            }
+            Expression::Call(call) => {
+                self.resolve_expression(&call.callee);
+                for argument in call.arguments.iter() {
+                    self.resolve_expression(argument);
+                }
+                self.check_arguments(&call.callee, call.arguments.len(), call.span);
+            }
+            Expression::CompoundAssignment(assignment) => {
+                self.check_assignment_target(&assignment.target, assignment.span);
+                self.resolve_expression(&assignment.target);
+                self.resolve_expression(&assignment.value);
+            }
+            Expression::Get(get) => self.resolve_expression(&get.object),
+            Expression::Grouping(grouping) => self.resolve_expression(&grouping.expression),
+            Expression::Index(index) => {
+                self.resolve_expression(&index.object);
+                self.resolve_expression(&index.index);
+            }
+            Expression::Interpolation(interpolation) => {
+                for part in interpolation.parts.iter() {
+                    if let InterpolationPart::Expression(expression) = part {
+                        self.resolve_expression(expression);
+                    }
+                }
+            }
+            Expression::Lambda(lambda) => {
+                self.resolve_function(&lambda.params, &lambda.body, FunctionKind::Function)
+            }
+            Expression::List(list) => {
+                for element in list.elements.iter() {
+                    self.resolve_expression(element);
+                }
+            }
+            Expression::Literal(_) => {}
+            Expression::Variable(variable) => self.check_initialized(variable.name, variable.span),
+            Expression::Logical(logical) => {
+                self.resolve_expression(&logical.left);
+                self.resolve_expression(&logical.right);
+            }
+            Expression::Map(map) => {
+                for entry in map.entries.iter() {
+                    self.resolve_expression(&entry.key);
+                    self.resolve_expression(&entry.value);
+                }
+            }
+            Expression::Range(range) => {
+                self.resolve_expression(&range.start);
+                self.resolve_expression(&range.end);
+            }
+            Expression::Super(super_) => {
+                let message = match self.classes.last() {
+                    None => Some("Can't use 'super' outside of a class"),
+                    Some(class) if !class.has_superclass => {
+                        Some("Can't use 'super' in a class with no superclass")
+                    }
+                    Some(_) => None,
+                };
+                if let Some(message) = message {
+                    self.errors.push(
+                        LoxError::new(super_.span.line(self.source), message.to_string())
+                            .with_span(super_.span),
+                    );
+                }
+            }
+            Expression::This(this) => {
+                let message = match self.classes.last() {
+                    None => Some("Can't use 'this' outside of a class"),
+                    Some(class) if class.in_static_method => {
+                        Some("Can't use 'this' in a static method")
+                    }
+                    Some(_) => None,
+                };
+                if let Some(message) = message {
+                    self.errors.push(
+                        LoxError::new(this.span.line(self.source), message.to_string())
+                            .with_span(this.span),
+                    );
+                }
+            }
+            Expression::Ternary(ternary) => {
+                self.resolve_expression(&ternary.condition);
+                self.resolve_expression(&ternary.true_branch);
+                self.resolve_expression(&ternary.false_branch);
+            }
+            Expression::Unary(unary) => self.resolve_expression(&unary.right),
+            Expression::Yield(yield_) => {
+                if let Some(value) = &yield_.value {
+                    self.resolve_expression(value);
+                }
+            }
+            Expression::Update(update) => {
+                self.check_assignment_target(&update.target, update.span);
+                self.resolve_expression(&update.target);
+            }
+        }
+    }
+
+    /// Reject uses of a `let` or `const` before its declaration has run.
+    /// A use inside a function declared earlier is fine as long as the
+    /// function is only called later, which can't be known statically.
+    fn check_initialized(&mut self, name: &str, span: Span) {
+        let Some((binding, function_depth)) = self.lookup(name) else {
+            return;
+        };
+        if !binding.initialized && function_depth == self.function_depth {
+            let declaration = binding.span;
+            self.errors.push(
+                LoxError::new(
+                    span.line(self.source),
+                    format!("Cannot access '{}' before its declaration", name),
+                )
+                .with_span(span)
+                .with_label(declaration, format!("'{}' is declared here", name)),
+            );
+        }
+    }
+
+    /// Reject `Color.Red` patterns unless `Color` is an enum in scope with
+    /// a `Red` variant
+    fn check_variant_patterns(&mut self, pattern: &Pattern<'alloc>) {
+        match pattern {
+            Pattern::Variant(variant) => {
+                let message = match self.lookup(variant.enum_name) {
+                    Some((
+                        Binding {
+                            variants: Some(variants),
+                            ..
+                        },
+                        _,
+                    )) => (!variants.contains(&variant.variant)).then(|| {
+                        format!(
+                            "'{}' is not a variant of enum '{}'",
+                            variant.variant, variant.enum_name
+                        )
+                    }),
+                    _ => Some(format!("'{}' is not an enum in scope", variant.enum_name)),
+                };
+                if let Some(message) = message {
+                    self.errors.push(
+                        LoxError::new(variant.span.line(self.source), message)
+                            .with_span(variant.span),
+                    );
+                }
+            }
+            Pattern::List(list) => {
+                for element in list.elements.iter() {
+                    self.check_variant_patterns(element);
+                }
+            }
+            Pattern::Map(map) => {
+                for entry in map.entries.iter() {
+                    self.check_variant_patterns(&entry.pattern);
+                }
+            }
+            Pattern::Binding(_) | Pattern::Literal(_) | Pattern::Wildcard(_) => {}
+        }
+    }
+
+    /// Reject calls to a known function with the wrong number of arguments
+    fn check_arguments(&mut self, callee: &Expression<'alloc>, count: usize, span: Span) {
+        let Expression::Variable(variable) = callee else {
+            return;
+        };
+        let Some((
+            &Binding {
+                arity: Some(arity),
+                span: declaration,
+                ..
+            },
+            _,
+        )) = self.lookup(variable.name)
+        else {
+            return;
+        };
+        if !arity.accepts(count) {
+            // Agrees with the last number shown, "at least 1 argument"
+            let noun = if arity.max.unwrap_or(arity.min) == 1 {
+                "argument"
+            } else {
+                "arguments"
+            };
+            self.errors.push(
+                LoxError::new(
+                    span.line(self.source),
+                    format!(
+                        "'{}' expects {} {} but got {}",
+                        variable.name, arity, noun, count
+                    ),
+                )
+                .with_span(span)
+                .with_label(declaration, format!("'{}' is declared here", variable.name)),
+            );
+        }
+    }
+
+    /// Reject writes to a `const`, pointing at both the write and the declaration.
+    /// Consts are declared when their block is entered, so a function
+    /// written before the const is checked against it too.
+    fn check_assignment_target(&mut self, target: &Expression<'alloc>, span: Span) {
+        let Expression::Variable(variable) = target else {
+            return;
+        };
+        let Some((binding, _)) = self.lookup(variable.name) else {
+            return;
+        };
+        let (kind, declaration) = (binding.kind, binding.span);
+        self.reassigned.insert(declaration.from);
+        if let DeclarationKind::Const = kind {
+            self.errors.push(
+                LoxError::new(
+                    span.line(self.source),
+                    format!("Cannot assign to constant '{}'", variable.name),
+                )
+                .with_span(span)
+                .with_label(declaration, format!("'{}' is declared here", variable.name)),
+            );
+        }
+    }
+}